@@ -0,0 +1,4 @@
+// Rebuild when a migration is added, since they're embedded into the binary.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,39 @@
+-- Schema as it was before migrations were introduced. Existing databases already have these tables.
+CREATE TABLE IF NOT EXISTS queues (
+    channel_id BIGINT PRIMARY KEY,
+    message_id BIGINT NOT NULL,
+    region TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    excluded_regions TEXT[] NOT NULL DEFAULT '{}',
+    fill_threshold BIGINT,
+    time_threshold BIGINT,
+    ping_channel BIGINT,
+    ping_role BIGINT,
+    regex_filters TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE TABLE IF NOT EXISTS user_data (
+    queue      BIGINT NOT NULL,
+    user_id     BIGINT NOT NULL,
+    nation     TEXT   NOT NULL,
+    founded    BIGINT NOT NULL,
+    newfounds  TEXT[] NOT NULL,
+    refounds   TEXT[] NOT NULL,
+
+    CONSTRAINT user_data_pkey PRIMARY KEY (queue, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS delivery_reports (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    event TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    queue BIGINT NOT NULL,
+    queue_time BIGINT NOT NULL,
+    recruiter BIGINT NOT NULL,
+    sender TEXT NOT NULL,
+    template TEXT NOT NULL,
+    sent_time BIGINT NOT NULL,
+    moved BOOLEAN DEFAULT FALSE,
+    moved_time BIGINT
+);
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS queued_nations (
+    id BIGSERIAL PRIMARY KEY,
+    queue BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    region TEXT NOT NULL,
+    event TEXT NOT NULL,
+    queue_time BIGINT NOT NULL,
+
+    CONSTRAINT queued_nations_queue_name_key UNIQUE (queue, name)
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    queue               BIGINT NOT NULL,
+    user_id             BIGINT NOT NULL,
+    delay               BIGINT,
+    last_activity_check BIGINT NOT NULL,
+    pause_time          BIGINT,
+
+    CONSTRAINT sessions_pkey PRIMARY KEY (queue, user_id)
+);
+
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS batch_size BIGINT NOT NULL DEFAULT 8;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS drop_moved BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE user_data ADD COLUMN IF NOT EXISTS batch_size BIGINT;
+ALTER TABLE user_data ADD COLUMN IF NOT EXISTS adaptive BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE user_data ADD COLUMN IF NOT EXISTS suspended BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE delivery_reports ADD COLUMN IF NOT EXISTS template_label TEXT;
@@ -0,0 +1,49 @@
+CREATE TABLE IF NOT EXISTS templates (
+    id       BIGSERIAL PRIMARY KEY,
+    queue    BIGINT  NOT NULL,
+    user_id  BIGINT  NOT NULL,
+    code     TEXT    NOT NULL,
+    label    TEXT    NOT NULL,
+    category TEXT    NOT NULL,
+    enabled  BOOLEAN NOT NULL DEFAULT TRUE,
+    weight   INTEGER NOT NULL DEFAULT 1,
+
+    CONSTRAINT templates_code_key UNIQUE (queue, user_id, code)
+);
+
+-- Templates used to be stored as arrays of codes on user_data, with only their weights stored here.
+-- A template listed in both arrays becomes a template for both categories.
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns WHERE table_name = 'user_data' AND column_name = 'newfounds'
+    ) THEN
+        ALTER TABLE templates ADD COLUMN IF NOT EXISTS label TEXT;
+        ALTER TABLE templates ADD COLUMN IF NOT EXISTS category TEXT;
+        ALTER TABLE templates ADD COLUMN IF NOT EXISTS enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+        -- Weighted templates already have a row, which keeps its weight
+        INSERT INTO templates (queue, user_id, code, label, category)
+        SELECT queue, user_id, code, code, CASE
+            WHEN bool_or(category = 'newfound') AND bool_or(category = 'refound') THEN 'both'
+            ELSE min(category)
+        END FROM (
+            SELECT queue, user_id, unnest(newfounds) AS code, 'newfound' AS category FROM user_data
+            UNION ALL
+            SELECT queue, user_id, unnest(refounds) AS code, 'refound' AS category FROM user_data
+        ) AS old_templates
+        GROUP BY queue, user_id, code
+        ON CONFLICT (queue, user_id, code) DO UPDATE
+        SET label = EXCLUDED.label,
+        category = EXCLUDED.category;
+
+        -- Weights left behind by templates that were no longer in use
+        DELETE FROM templates WHERE category IS NULL;
+
+        ALTER TABLE templates ALTER COLUMN label SET NOT NULL;
+        ALTER TABLE templates ALTER COLUMN category SET NOT NULL;
+
+        ALTER TABLE user_data DROP COLUMN newfounds;
+        ALTER TABLE user_data DROP COLUMN refounds;
+    END IF;
+END $$;
@@ -0,0 +1 @@
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS group_code TEXT;
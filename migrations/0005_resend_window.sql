@@ -0,0 +1,6 @@
+-- Existing queues keep queueing every nation, only new queues skip recent resends by default
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS resend_window BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE queues ALTER COLUMN resend_window SET DEFAULT 30;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS skipped_resends BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS delivery_reports_name_queue_idx ON delivery_reports (name, queue);
@@ -0,0 +1,2 @@
+ALTER TABLE queues RENAME COLUMN excluded_regions TO filter_regions;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS region_filter_mode TEXT NOT NULL DEFAULT 'exclude';
@@ -0,0 +1 @@
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS name_rules TEXT[] NOT NULL DEFAULT '{numbered,roman_numeral}';
@@ -0,0 +1,4 @@
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS reminder_cooldown BIGINT NOT NULL DEFAULT 360;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS quiet_start INTEGER;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS quiet_end INTEGER;
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS timezone TEXT NOT NULL DEFAULT 'UTC';
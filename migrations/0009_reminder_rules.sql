@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS reminder_rules (
+    id             BIGSERIAL PRIMARY KEY,
+    queue          BIGINT NOT NULL,
+    fill_threshold BIGINT NOT NULL,
+    time_threshold BIGINT NOT NULL,
+    ping_channel   BIGINT,
+    ping_role      BIGINT,
+    message        TEXT
+);
+
+-- Queues used to have a single threshold pair, role and channel, which become their first rule.
+-- Reminders were only sent with both a role and a channel, so rules without a role get no channel.
+INSERT INTO reminder_rules (queue, fill_threshold, time_threshold, ping_channel, ping_role)
+SELECT channel_id, fill_threshold, time_threshold, CASE WHEN ping_role IS NOT NULL THEN ping_channel END, ping_role FROM queues
+WHERE fill_threshold IS NOT NULL AND time_threshold IS NOT NULL;
+
+ALTER TABLE queues DROP COLUMN fill_threshold;
+ALTER TABLE queues DROP COLUMN time_threshold;
+ALTER TABLE queues DROP COLUMN ping_channel;
+ALTER TABLE queues DROP COLUMN ping_role;
@@ -0,0 +1 @@
+ALTER TABLE reminder_rules ADD COLUMN IF NOT EXISTS embed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS admin_roles (
+    guild_id BIGINT NOT NULL,
+    role_id  BIGINT NOT NULL,
+
+    PRIMARY KEY (guild_id, role_id)
+);
@@ -0,0 +1 @@
+ALTER TABLE queues ADD COLUMN IF NOT EXISTS share_statistics BOOLEAN NOT NULL DEFAULT FALSE;
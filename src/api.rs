@@ -1,5 +1,7 @@
-use log::info;
+use log::{info, warn};
 use caramel::ns::api::Client;
+use lazy_static::lazy_static;
+use regex::Regex;
 use serde::Deserialize;
 use serenity::all::Timestamp;
 
@@ -28,9 +30,144 @@ pub async fn query_nation_data(
     return Ok(data);
 }
 
+// Whether a query failed because the nation doesn't exist, which the API reports with a 404 status.
+// Any other failure, including ones without an HTTP status, is treated as transient.
+pub fn is_not_found(err: &crate::bot::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err.as_ref());
+
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            return err.status() == Some(reqwest::StatusCode::NOT_FOUND);
+        }
+
+        source = err.source();
+    }
+
+    false
+}
+
+#[derive(Deserialize)]
+struct WorldData {
+    #[serde(rename = "HAPPENINGS")]
+    happenings: HappeningList,
+}
+
+#[derive(Deserialize)]
+struct HappeningList {
+    #[serde(rename = "EVENT", default)]
+    events: Vec<Happening>,
+}
+
+#[derive(Deserialize)]
+struct Happening {
+    #[serde(rename = "TIMESTAMP")]
+    timestamp: i64,
+    #[serde(rename = "TEXT")]
+    text: String,
+}
+
+#[derive(Debug)]
+pub struct FoundingEvent {
+    pub nation: String,
+    pub region: String,
+    pub event: String,
+    pub time: i64,
+}
+
+lazy_static! {
+    static ref FOUNDING_RE: Regex = Regex::new(r#"^@@([0-9a-z_-]+)@@ was (founded|refounded) in %%([0-9a-z_-]+)%%\.$"#).unwrap();
+}
+
+const HAPPENINGS_LIMIT: usize = 200; // Maximum amount of events the API returns in one request
+const MAX_HAPPENINGS_PAGES: usize = 25; // Backfills stop after 5000 events
+
+// Founding events parsed from one response, with the amount and oldest timestamp of all happenings in it.
+pub struct FoundingPage {
+    pub events: Vec<FoundingEvent>,
+    pub happenings: usize,
+    pub oldest: Option<i64>,
+}
+
+pub fn parse_founding_events(xml: &str) -> Result<FoundingPage, crate::bot::Error> {
+    let data = quick_xml::de::from_str::<WorldData>(xml)?;
+
+    let happenings = data.happenings.events.len();
+    let oldest = data.happenings.events.iter().map(|v| v.timestamp).min();
+
+    let mut events = data.happenings.events.into_iter().filter_map(|happening| {
+        let captures = FOUNDING_RE.captures(happening.text.trim())?;
+
+        Some(FoundingEvent {
+            nation: captures[1].to_owned(),
+            region: captures[3].to_owned(),
+            event: match &captures[2] {
+                "founded" => "newfound",
+                _ => "refound",
+            }.to_owned(),
+            time: happening.timestamp,
+        })
+    }).collect::<Vec<_>>();
+
+    // The API returns the newest events first
+    events.sort_by_key(|v| v.time);
+
+    Ok(FoundingPage { events, happenings, oldest })
+}
+
+// Queries founding and refounding happenings, optionally only those after `since`, paging back from
+// the most recent ones. Events are returned in chronological order.
+pub async fn query_founding_events(
+    client: &Client, since: Option<i64>
+) -> Result<Vec<FoundingEvent>, crate::bot::Error> {
+    let since = since.map(|v| (v + 1).to_string());
+    let limit = HAPPENINGS_LIMIT.to_string();
+
+    let mut events = Vec::new();
+    let mut before: Option<String> = None;
+
+    for _ in 0..MAX_HAPPENINGS_PAGES {
+        let mut query = vec![
+            ("q", "happenings"), ("filter", "founding"), ("limit", limit.as_str())
+        ];
+
+        if let Some(since) = &since {
+            query.push(("sincetime", since));
+        }
+
+        if let Some(before) = &before {
+            query.push(("beforetime", before));
+        }
+
+        let response = client.make_request_with_retry(query).await?;
+
+        let page = parse_founding_events(&response)?;
+        events.extend(page.events);
+
+        // Events sharing the oldest timestamp may continue on the next page, so it's queried again
+        match page.oldest {
+            Some(oldest) if page.happenings >= HAPPENINGS_LIMIT => before = Some((oldest + 1).to_string()),
+            _ => {
+                before = None;
+                break;
+            }
+        }
+    }
+
+    if let Some(before) = before {
+        warn!("Stopped querying founding events after {} requests, events before {} were skipped", MAX_HAPPENINGS_PAGES, before);
+    }
+
+    events.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.nation.cmp(&b.nation)));
+    events.dedup_by(|a, b| a.time == b.time && a.nation == b.nation);
+
+    info!("Queried {} founding events since {:?}", events.len(), since);
+
+    Ok(events)
+}
+
 const MIN_COOLDOWN_AGE: i64 = 47174400; // 18 months
-const MAX_DELAY: i64 = 14; // Youngest nations get 14 seconds per nation, 112 seconds for am 8-nation batch
-const MIN_DELAY: i64 = 5; // Older nations get 5 seconds per nation, 40 seconds for an 8-nation batch
+const MAX_DELAY: i64 = 14; // Youngest nations wait 14 seconds per recipient
+const MIN_DELAY: i64 = 5; // Nations older than 18 months wait 5 seconds per recipient
 
 // Calculates the per-recipient telegram delay from a nation according to when it was founded.
 // Maps time since founding between newly founded and 18 months old linearly to a per-recipient 
@@ -41,4 +178,48 @@ pub fn calculate_telegram_delay(foundedtime: i64) -> i64 {
     let range = MAX_DELAY - MIN_DELAY;
 
     MAX_DELAY - ((seconds * range) / MIN_COOLDOWN_AGE)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAPPENINGS: &str = r#"<WORLD><HAPPENINGS>
+<EVENT id="3"><TIMESTAMP>1700000300</TIMESTAMP><TEXT>@@new_nation@@ was founded in %%the_north_pacific%%.</TEXT></EVENT>
+<EVENT id="2"><TIMESTAMP>1700000200</TIMESTAMP><TEXT>@@some_nation@@ relocated from %%lazarus%% to %%osiris%%.</TEXT></EVENT>
+<EVENT id="1"><TIMESTAMP>1700000100</TIMESTAMP><TEXT>@@old_nation@@ was refounded in %%lazarus%%.</TEXT></EVENT>
+</HAPPENINGS></WORLD>"#;
+
+    #[test]
+    fn parses_founding_events_in_chronological_order() {
+        let page = parse_founding_events(HAPPENINGS).unwrap();
+
+        assert_eq!(page.events.len(), 2);
+
+        assert_eq!(page.events[0].nation, "old_nation");
+        assert_eq!(page.events[0].region, "lazarus");
+        assert_eq!(page.events[0].event, "refound");
+        assert_eq!(page.events[0].time, 1700000100);
+
+        assert_eq!(page.events[1].nation, "new_nation");
+        assert_eq!(page.events[1].region, "the_north_pacific");
+        assert_eq!(page.events[1].event, "newfound");
+        assert_eq!(page.events[1].time, 1700000300);
+    }
+
+    #[test]
+    fn counts_every_happening_on_the_page() {
+        let page = parse_founding_events(HAPPENINGS).unwrap();
+
+        assert_eq!(page.happenings, 3);
+        assert_eq!(page.oldest, Some(1700000100));
+    }
+
+    #[test]
+    fn parses_empty_happenings() {
+        let page = parse_founding_events("<WORLD><HAPPENINGS></HAPPENINGS></WORLD>").unwrap();
+
+        assert!(page.events.is_empty());
+        assert_eq!(page.happenings, 0);
+        assert_eq!(page.oldest, None);
+    }
+}
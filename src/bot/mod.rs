@@ -1,14 +1,14 @@
 pub mod util;
 mod handler;
 
-use serenity::all::{ComponentInteraction, ClientBuilder, ChannelId, UserId, GatewayIntents};
+use serenity::all::{ComponentInteraction, ClientBuilder, ChannelId, UserId, GatewayIntents, GuildId, Member, RoleId};
 use sqlx::PgPool;
 use tokio::sync::Mutex;
 use std::{collections::HashMap, error::Error as StdError, sync::Arc};
 
 use caramel::ns::{UserAgent, api::Client};
 
-use crate::{config::Config, models::{queue::Queue, session::Session, user_data::UserData}};
+use crate::{config::Config, models::{admin_role::AdminRole, queue::Queue, session::Session, user_data::UserData}};
 use crate::commands::create_command_list;
 
 use handler::event_handler;
@@ -17,13 +17,14 @@ pub struct InnerData {
     pub pool: PgPool,
     pub user_agent: UserAgent,
     pub queues: Mutex<HashMap<ChannelId, Queue>>,
-    pub sessions: Mutex<HashMap<UserId, Session>>,
+    pub sessions: Mutex<HashMap<(ChannelId, UserId), Session>>,
     pub user_data: Mutex<HashMap<(ChannelId, UserId), UserData>>,
-    pub cooldowns: Mutex<HashMap<UserId, (i64, Option<ComponentInteraction>)>>,
-    pub channel: lapin::Channel,
+    pub cooldowns: Mutex<HashMap<String, (i64, Option<ComponentInteraction>)>>,
+    pub rabbitmq_url: String,
     pub config: Config,
     pub api_client: Client,
     pub interaction_tokens: Mutex<HashMap<String, String>>,
+    pub admin_roles: Mutex<HashMap<GuildId, Vec<RoleId>>>,
 }
 
 #[derive(Clone)]
@@ -37,7 +38,9 @@ impl Data {
         user_agent: UserAgent,
         queues: HashMap<ChannelId, Queue>,
         user_data: HashMap<(ChannelId, UserId), UserData>,
-        channel: lapin::Channel,
+        sessions: HashMap<(ChannelId, UserId), Session>,
+        admin_roles: HashMap<GuildId, Vec<RoleId>>,
+        rabbitmq_url: String,
         config: Config,
         api_client: Client,
     ) -> Self {
@@ -46,16 +49,28 @@ impl Data {
                 pool,
                 user_agent: user_agent.clone(),
                 queues: Mutex::new(queues),
-                sessions: Mutex::new(HashMap::new()),
+                sessions: Mutex::new(sessions),
                 user_data: Mutex::new(user_data),
                 cooldowns: Mutex::new(HashMap::new()),
-                channel,
+                rabbitmq_url,
                 config,
                 api_client,
                 interaction_tokens: Mutex::new(HashMap::new()),
+                admin_roles: Mutex::new(admin_roles),
             }),
         }
     }
+
+    // Members with Manage Server, or with one of the guild's admin roles, can administer queues.
+    pub async fn is_queue_admin(&self, member: &Member) -> bool {
+        if member.permissions.is_some_and(|p| p.manage_guild()) {
+            return true;
+        }
+
+        self.inner.admin_roles.lock().await.get(&member.guild_id).is_some_and(|roles| {
+            member.roles.iter().any(|role| roles.contains(role))
+        })
+    }
 }
 
 pub type Error = Box<dyn StdError + Send + Sync>;
@@ -65,7 +80,7 @@ pub async fn start_client(
     token: String,
     pool: PgPool,
     user_agent: UserAgent,
-    channel: lapin::Channel,
+    rabbitmq_url: String,
     config: Config,
     client: Client
 ) -> Result<(), Error> {
@@ -85,7 +100,9 @@ pub async fn start_client(
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
                 let queues = Queue::query(&pool).await?;
                 let user_data= UserData::query(&pool).await?;
-                Ok(Data::new(pool, user_agent, queues, user_data, channel, config, client))
+                let sessions = Session::query(&pool).await?;
+                let admin_roles = AdminRole::query(&pool).await?;
+                Ok(Data::new(pool, user_agent, queues, user_data, sessions, admin_roles, rabbitmq_url, config, client))
             })
         }).build();
 
@@ -1,5 +1,5 @@
 use serenity::all::{
-    CacheHttp, ComponentInteraction, Context, CreateInteractionResponse, 
+    CacheHttp, ComponentInteraction, Context, CreateActionRow, CreateEmbed, CreateInteractionResponse, 
     CreateInteractionResponseMessage, EditInteractionResponse, ModalInteraction
 };
 
@@ -44,4 +44,15 @@ pub async fn edit_reply(
     }?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Replaces the embed and components of the message the component is attached to.
+pub async fn update_message(
+    ctx: &Context, component: &ComponentInteraction, embed: CreateEmbed, components: Vec<CreateActionRow>
+) -> Result<(), crate::bot::Error> {
+    let message = CreateInteractionResponseMessage::new().embed(embed).components(components);
+
+    component.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(message)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use itertools::Itertools;
+use poise::CreateReply;
+use serenity::all::{Mentionable, Role};
+
+use crate::bot::{Context, Error};
+use crate::commands::check_manage_guild_authorization;
+use crate::models::admin_role::AdminRole;
+
+#[poise::command(slash_command, subcommands("add_admin_role", "remove_admin_role", "list_admin_roles"))]
+pub async fn admin_roles(
+    _: Context<'_>,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, rename = "add")]
+pub async fn add_admin_role(
+    ctx: Context<'_>,
+    #[description = "Role allowed to create, edit and delete queues"] role: Role,
+) -> Result<(), Error> {
+    if !check_manage_guild_authorization(&ctx).await? {
+        return Ok(());
+    }
+
+    let exists = ctx.data().inner.admin_roles.lock().await.get(&role.guild_id).is_some_and(
+        |roles| roles.contains(&role.id)
+    );
+
+    if exists {
+        return reply(ctx, &format!("{} is already an admin role.", role.mention())).await;
+    }
+
+    AdminRole::new(role.guild_id, role.id).insert(&ctx.data().inner.pool).await;
+    ctx.data().inner.admin_roles.lock().await.entry(role.guild_id).or_default().push(role.id);
+
+    reply(ctx, &format!("Members with {} can now administer queues in this server.", role.mention())).await
+}
+
+#[poise::command(slash_command, rename = "remove")]
+pub async fn remove_admin_role(
+    ctx: Context<'_>,
+    #[description = "Role to remove from the admin roles"] role: Role,
+) -> Result<(), Error> {
+    if !check_manage_guild_authorization(&ctx).await? {
+        return Ok(());
+    }
+
+    let removed = {
+        let mut admin_roles = ctx.data().inner.admin_roles.lock().await;
+        let roles = admin_roles.entry(role.guild_id).or_default();
+        let count = roles.len();
+
+        roles.retain(|v| *v != role.id);
+        roles.len() != count
+    };
+
+    if !removed {
+        return reply(ctx, &format!("{} is not an admin role.", role.mention())).await;
+    }
+
+    AdminRole::new(role.guild_id, role.id).remove(&ctx.data().inner.pool).await;
+
+    reply(ctx, &format!("{} can no longer administer queues in this server.", role.mention())).await
+}
+
+#[poise::command(slash_command, rename = "list")]
+pub async fn list_admin_roles(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    if !check_manage_guild_authorization(&ctx).await? {
+        return Ok(());
+    }
+
+    let Some(guild) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    let roles = ctx.data().inner.admin_roles.lock().await.get(&guild).cloned().unwrap_or_default();
+
+    if roles.is_empty() {
+        return reply(ctx, "There are no admin roles, only members with Manage Server can administer queues.").await;
+    }
+
+    reply(ctx, &format!(
+        "Besides members with Manage Server, these roles can administer queues: {}", 
+        roles.iter().map(|role| role.mention()).join(", ")
+    )).await
+}
+
+async fn reply(ctx: Context<'_>, content: &str) -> Result<(), Error> {
+    ctx.send(
+        CreateReply::default().content(content).ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
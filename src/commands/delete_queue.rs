@@ -2,7 +2,7 @@ use poise::CreateReply;
 use log::warn;
 
 use crate::bot::{Context, Error};
-use crate::models::user_data::UserData;
+use crate::models::{reminder::ReminderRule, session::Session, user_data::UserData};
 use crate::commands::check_command_authorization;
 
 #[poise::command(slash_command)]
@@ -55,6 +55,8 @@ pub async fn delete_queue(
 
     queue.remove(&ctx.data().inner.pool).await;
     UserData::remove_matching(queue.channel.get() as i64, &ctx.data().inner.pool).await;
+    Session::remove_matching(queue.channel.get() as i64, &ctx.data().inner.pool).await;
+    ReminderRule::remove_matching(queue.channel.get() as i64, &ctx.data().inner.pool).await;
 
     ctx.http().delete_message(queue.channel, queue.message, None).await.unwrap_or_else(|err| {
         warn!("Failed to delete queue message: {}", err);
@@ -22,9 +22,7 @@ pub async fn edit_queue(
         return Ok(());
     };
 
-    let (embed, components) = create_edit_queue_embed(
-        &queue.region, queue.size, &queue.filter.regions, &queue.thresholds, &queue.ping_channel, &queue.ping_role, &queue.filter.regexes
-    );
+    let (embed, components) = create_edit_queue_embed(queue);
 
     drop(queues);
 
@@ -0,0 +1,33 @@
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, FormattedTimestamp, FormattedTimestampStyle};
+
+use crate::bot::{Context, Error};
+use crate::feed;
+use crate::commands::check_command_authorization;
+
+#[poise::command(slash_command)]
+pub async fn feed_status(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    if !check_command_authorization(&ctx).await? {
+        return Ok(());
+    }
+
+    let status = match feed::disconnected_since() {
+        Some(since) => format!(
+            "Disconnected since {}", FormattedTimestamp::new(since, Some(FormattedTimestampStyle::RelativeTime))
+        ),
+        None => "Connected".to_string(),
+    };
+
+    let embed = CreateEmbed::new().title("Event Feed Status").fields(vec![
+        ("Status", status, false),
+        ("Rejected Events", format!("`{}` malformed events skipped since startup", feed::rejected_events()), false),
+    ]);
+
+    ctx.send(
+        CreateReply::default().embed(embed).ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
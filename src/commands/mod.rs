@@ -1,6 +1,9 @@
 mod create_queue;
 mod edit_queue;
 mod delete_queue;
+mod feed_status;
+mod template;
+mod admin_roles;
 
 use poise::{CreateReply, Command};
 use crate::bot::{Context, Error, Data};
@@ -8,16 +11,32 @@ use crate::bot::{Context, Error, Data};
 use create_queue::create_queue;
 use edit_queue::edit_queue;
 use delete_queue::delete_queue;
+use feed_status::feed_status;
+use template::template;
+use admin_roles::admin_roles;
 
 pub fn create_command_list() -> Vec<Command<Data, Error>> {
     vec![
         create_queue(),
         edit_queue(),
-        delete_queue()
+        delete_queue(),
+        feed_status(),
+        template(),
+        admin_roles()
     ]
 }
 
+// Allows members with Manage Server or one of the guild's admin roles.
 pub async fn check_command_authorization(ctx: &Context<'_>) -> Result<bool, Error> {
+    check_authorization(ctx, true).await
+}
+
+// Allows only members with Manage Server, e.g. for managing the admin roles themselves.
+pub async fn check_manage_guild_authorization(ctx: &Context<'_>) -> Result<bool, Error> {
+    check_authorization(ctx, false).await
+}
+
+async fn check_authorization(ctx: &Context<'_>, allow_admin_roles: bool) -> Result<bool, Error> {
     match ctx.author_member().await {
         None => {
             ctx.send(
@@ -27,7 +46,13 @@ pub async fn check_command_authorization(ctx: &Context<'_>) -> Result<bool, Erro
             return Ok(false);
         },
         Some(member) => {
-            if !member.permissions.map_or(false, |p| p.manage_guild()) {
+            let allowed = if allow_admin_roles {
+                ctx.data().is_queue_admin(&member).await
+            } else {
+                member.permissions.is_some_and(|p| p.manage_guild())
+            };
+
+            if !allowed {
                 ctx.send(
                     CreateReply::default().content("You are not allowed to run this command!").ephemeral(true)
                 ).await?;
@@ -0,0 +1,185 @@
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+use crate::bot::{Context, Error};
+use crate::models::template::{Template, TemplateCategory, DEFAULT_TEMPLATE_WEIGHT, MAX_TEMPLATE_WEIGHT};
+
+#[poise::command(slash_command, subcommands("add_template", "enable_template", "disable_template", "remove_template", "list_templates"))]
+pub async fn template(
+    _: Context<'_>,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, rename = "add")]
+pub async fn add_template(
+    ctx: Context<'_>,
+    #[description = "Template code, e.g. %TEMPLATE-123456%"] code: String,
+    #[description = "Name shown in telegrams and reports"] label: Option<String>,
+    #[description = "Nations this template is used for"]
+    #[choices("newfound", "refound", "both")] category: &'static str,
+    #[description = "Relative chance of picking this template"] weight: Option<u32>,
+) -> Result<(), Error> {
+    let code = code.trim().to_string();
+    let weight = weight.unwrap_or(DEFAULT_TEMPLATE_WEIGHT);
+
+    if !Template::is_valid_code(&code) {
+        return reply(ctx, &format!("Error: {} is not a valid template code (expected e.g. %TEMPLATE-123456%)", code)).await;
+    }
+
+    if !(1..=MAX_TEMPLATE_WEIGHT).contains(&weight) {
+        return reply(ctx, &format!("Error: weight must be a number between 1 and {}", MAX_TEMPLATE_WEIGHT)).await;
+    }
+
+    let category = TemplateCategory::parse(category).unwrap_or(TemplateCategory::Both);
+    let label = label.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).unwrap_or(code.clone());
+
+    let mut user_data = ctx.data().inner.user_data.lock().await;
+
+    let Some(data) = user_data.get_mut(&(ctx.channel_id(), ctx.author().id)) else {
+        drop(user_data);
+        return reply(ctx, "You have not set up your templates for this queue yet!").await;
+    };
+
+    let existing = data.templates.iter().position(|v| v.code == code);
+
+    let index = match existing {
+        Some(index) => {
+            let template = &mut data.templates[index];
+            template.label = label;
+            template.category = category;
+            template.enabled = true;
+            template.weight = weight;
+
+            index
+        },
+        None => {
+            data.templates.push(Template::new(code, label, category, weight));
+            data.templates.len() - 1
+        }
+    };
+
+    let template = &mut data.templates[index];
+
+    if template.insert(ctx.channel_id(), ctx.author().id, &ctx.data().inner.pool).await.is_err() {
+        if existing.is_none() {
+            data.templates.remove(index);
+        }
+
+        drop(user_data);
+        return reply(ctx, "Error: failed to save the template, please try again").await;
+    }
+
+    let message = format!("Template {} saved with ID {}.", template.label, template.id);
+    drop(user_data);
+
+    reply(ctx, &message).await
+}
+
+#[poise::command(slash_command, rename = "enable")]
+pub async fn enable_template(
+    ctx: Context<'_>,
+    #[description = "Template ID, as shown by /template list"] id: i64,
+) -> Result<(), Error> {
+    set_template_enabled(ctx, id, true).await
+}
+
+#[poise::command(slash_command, rename = "disable")]
+pub async fn disable_template(
+    ctx: Context<'_>,
+    #[description = "Template ID, as shown by /template list"] id: i64,
+) -> Result<(), Error> {
+    set_template_enabled(ctx, id, false).await
+}
+
+#[poise::command(slash_command, rename = "remove")]
+pub async fn remove_template(
+    ctx: Context<'_>,
+    #[description = "Template ID, as shown by /template list"] id: i64,
+) -> Result<(), Error> {
+    let mut user_data = ctx.data().inner.user_data.lock().await;
+
+    let Some(data) = user_data.get_mut(&(ctx.channel_id(), ctx.author().id)) else {
+        drop(user_data);
+        return reply(ctx, "You have not set up your templates for this queue yet!").await;
+    };
+
+    let Some(index) = data.templates.iter().position(|v| v.id == id) else {
+        drop(user_data);
+        return reply(ctx, &format!("Error: no template with ID {}", id)).await;
+    };
+
+    let template = data.templates.remove(index);
+    drop(user_data);
+
+    template.remove(&ctx.data().inner.pool).await;
+
+    reply(ctx, &format!("Template {} removed.", template.label)).await
+}
+
+#[poise::command(slash_command, rename = "list")]
+pub async fn list_templates(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let user_data = ctx.data().inner.user_data.lock().await;
+
+    let Some(data) = user_data.get(&(ctx.channel_id(), ctx.author().id)) else {
+        drop(user_data);
+        return reply(ctx, "You have not set up your templates for this queue yet!").await;
+    };
+
+    let description = if data.templates.is_empty() {
+        "No templates registered.".to_string()
+    } else {
+        data.templates.iter().map(|v| format!(
+            "**{}** - {} (`{}`), {}, weight {}{}",
+            v.id, v.label, v.code, v.category, v.weight, if v.enabled { "" } else { ", disabled" }
+        )).collect::<Vec<_>>().join("\n")
+    };
+
+    drop(user_data);
+
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new().title("Telegram Templates").description(description)
+        ).ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+async fn set_template_enabled(ctx: Context<'_>, id: i64, enabled: bool) -> Result<(), Error> {
+    let mut user_data = ctx.data().inner.user_data.lock().await;
+
+    let Some(data) = user_data.get_mut(&(ctx.channel_id(), ctx.author().id)) else {
+        drop(user_data);
+        return reply(ctx, "You have not set up your templates for this queue yet!").await;
+    };
+
+    let Some(template) = data.templates.iter_mut().find(|v| v.id == id) else {
+        drop(user_data);
+        return reply(ctx, &format!("Error: no template with ID {}", id)).await;
+    };
+
+    template.enabled = enabled;
+
+    if template.insert(ctx.channel_id(), ctx.author().id, &ctx.data().inner.pool).await.is_err() {
+        template.enabled = !enabled;
+
+        drop(user_data);
+        return reply(ctx, "Error: failed to save the template, please try again").await;
+    }
+
+    let message = format!("Template {} {}.", template.label, if enabled { "enabled" } else { "disabled" });
+    drop(user_data);
+
+    reply(ctx, &message).await
+}
+
+async fn reply(ctx: Context<'_>, content: &str) -> Result<(), Error> {
+    ctx.send(
+        CreateReply::default().content(content).ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
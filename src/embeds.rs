@@ -1,16 +1,18 @@
 use itertools::Itertools;
-use regex::Regex;
-use serenity::all::{ButtonStyle, ChannelId, ChannelType, CreateActionRow, CreateButton, CreateEmbed, CreateSelectMenu, CreateSelectMenuKind, FormattedTimestamp, FormattedTimestampStyle, Mentionable, RoleId, UserId};
+use serenity::all::{ButtonStyle, ChannelId, ChannelType, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, FormattedTimestamp, FormattedTimestampStyle, Mentionable, UserId};
 
 use caramel::ns::{UserAgent, format::prettify_name};
 
-use crate::models::{queue::{Nation, Queue}, session::{RecruitDelay}};
+use crate::feed;
+use crate::models::{queue::{FilterMode, NameRule, Nation, Queue}, reminder::{Reminder, ReminderRule, REMINDER_PLACEHOLDERS}, session::{RecruitDelay}, template::Template};
+
+const FIELD_VALUE_LIMIT: usize = 1024; // Discord's limit on the length of an embed field's value
 
 pub fn create_queue_embed(
     queue: &Queue,
     sessions: Vec<UserId>,
 ) -> (CreateEmbed, Vec<CreateActionRow>) {
-    let embed = CreateEmbed::new().title(
+    let mut embed = CreateEmbed::new().title(
         format!("{} Recruitment Center", prettify_name(&queue.region))
     ).fields(vec![
         ("Nations in Queue", format!("`{}`", &queue.amount_in_queue().to_string()), false),
@@ -29,6 +31,14 @@ pub fn create_queue_embed(
         }, false)
     ]);
 
+    if let Some(since) = feed::disconnected_since() {
+        embed = embed.field(
+            "⚠️ Event Feed Disconnected", 
+            format!("Since {}, new nations won't be added until it reconnects.", FormattedTimestamp::new(since, Some(FormattedTimestampStyle::RelativeTime))),
+            false
+        );
+    }
+
     let components = vec![
         CreateActionRow::Buttons(vec![
             CreateButton::new("recruit-oneshot").label("Recruit: Oneshot"),
@@ -45,16 +55,16 @@ pub fn create_queue_embed(
 
 pub fn create_telegram_embed(
     nations: &Vec<Nation>,
-    template: &String,
+    template: &Template,
     sender: &String,
     cooldown: i64,
     user_agent: &UserAgent,
-    add_stop_button: bool,
+    session_queue: Option<ChannelId>,
 ) -> (CreateEmbed, Vec<CreateActionRow>) {
     let embed = CreateEmbed::new().field(
         "Recipients", nations.iter().map(|v| &v.name).join(", "), false
     ).field(
-        "Template", format!("`{}`", template), true
+        "Template", format!("{} (`{}`)", template.label, template.code), true
     ).field(
         "Cooldown Ends", format!("<t:{}:R>", cooldown), true
     );
@@ -63,34 +73,38 @@ pub fn create_telegram_embed(
         "https://www.nationstates.net/container={}/nation={}/page=compose_telegram?tgto={}&message={}&generated_by={}",
         sender, sender,
         nations.iter().map(|v| &v.name).join(","),
-        urlencoding::encode(template).to_string(),
+        urlencoding::encode(&template.code),
         user_agent.web()
     )).label("Send Telegram")];
 
-    if add_stop_button {
+    if let Some(queue) = session_queue {
         row.push(
-            CreateButton::new("stream-end").style(ButtonStyle::Danger).label("Stop Session")
+            CreateButton::new(format!("stream-end:{}", queue)).style(ButtonStyle::Danger).label("Stop Session")
         );
     }
 
     (embed, vec![CreateActionRow::Buttons(row)])
 }
 
-pub fn create_pause_embed() -> (CreateEmbed, Vec<CreateActionRow>) {
+pub fn create_pause_embed(queue: ChannelId) -> (CreateEmbed, Vec<CreateActionRow>) {
     let embed = CreateEmbed::new().title("Still Here?").description(
-        "Recruitment session has been paused. Click Continue to resume it. Otherwise, the session will automatically be closed for inactivity in 5 minutes."
+        format!("Recruitment session for {} has been paused. Click Continue to resume it. Otherwise, the session will automatically be closed for inactivity in 5 minutes.", queue.mention())
     );
 
     let row = vec![
-        CreateButton::new("stream-resume").style(ButtonStyle::Success).label("Continue"),
-        CreateButton::new("stream-end").style(ButtonStyle::Danger).label("Stop Session")
+        CreateButton::new(format!("stream-resume:{}", queue)).style(ButtonStyle::Success).label("Continue"),
+        CreateButton::new(format!("stream-end:{}", queue)).style(ButtonStyle::Danger).label("Stop Session")
     ];
 
     (embed, vec![CreateActionRow::Buttons(row)])
 }
 
-pub fn create_statistics_embed() -> (CreateEmbed, Vec<CreateActionRow>) {
-    let embed = CreateEmbed::new().title("Recruitment Statistics");
+pub fn create_statistics_embed(queue: &Queue) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let embed = CreateEmbed::new().title("Recruitment Statistics").field(
+        "Skipped Nations", format!(
+            "`{}` nations weren't queued again because they were telegrammed recently", queue.skipped_resends
+        ), false
+    );
 
     let components = vec![
         CreateActionRow::Buttons(vec![
@@ -108,7 +122,8 @@ pub fn create_statistics_embed() -> (CreateEmbed, Vec<CreateActionRow>) {
 
 pub fn create_session_start_embed(
     nation: &String,
-    delay: &RecruitDelay
+    delay: &RecruitDelay,
+    queue: ChannelId,
 ) -> (CreateEmbed, Vec<CreateActionRow>) {
     let embed = CreateEmbed::new().title(
         "Session Started"
@@ -118,11 +133,36 @@ pub fn create_session_start_embed(
         "Started by", nation, true
     ).field(
         "Delay", delay.to_string(), true
+    ).field(
+        "Queue", queue.mention().to_string(), true
+    );
+
+    let components = vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("stream-end:{}", queue)).label("Stop Session").style(ButtonStyle::Danger),
+        ]),
+    ];
+
+    (embed, components)
+}
+
+pub fn create_session_restored_embed(
+    delay: &RecruitDelay,
+    queue: ChannelId,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let embed = CreateEmbed::new().title(
+        "Session Continued"
+    ).description(
+        "Vanille was restarted, and your recruitment session has been continued. Press the 'Stop Session' button to end it."
+    ).field(
+        "Delay", delay.to_string(), true
+    ).field(
+        "Queue", queue.mention().to_string(), true
     );
 
     let components = vec![
         CreateActionRow::Buttons(vec![
-            CreateButton::new("stream-end").label("Stop Session").style(ButtonStyle::Danger),
+            CreateButton::new(format!("stream-end:{}", queue)).label("Stop Session").style(ButtonStyle::Danger),
         ]),
     ];
 
@@ -130,60 +170,202 @@ pub fn create_session_start_embed(
 }
 
 pub fn create_edit_queue_embed(
-    region: &String,
-    size: usize,
-    filter: &Vec<String>,
-    thresholds: &Option<(u64, u64)>,
-    ping_channel: &Option<ChannelId>,
-    ping_role: &Option<RoleId>,
-    regex_filters: &Vec<Regex>,
+    queue: &Queue,
 ) -> (CreateEmbed, Vec<CreateActionRow>) {
     let embed = CreateEmbed::new().title(
-        format!("Editing Queue: {}", prettify_name(&region))
+        format!("Editing Queue: {}", prettify_name(&queue.region))
     ).field(
-        "Maximum Size", size.to_string(), false
+        "Maximum Size", queue.size.to_string(), false
     ).field(
-        "Excluded Regions", if filter.is_empty() { "None".into() } else { filter.iter().join(", ") }, false
+        "Batch Size", queue.batch_size.to_string(), false
     ).field(
-        "Reminder Threshold", thresholds.map_or(
-            "No reminders".into(), |(fill, time)| format!("Queue over {fill} nations and last telegram over {time} minutes")
-        ), false
+        "Resend Window", if queue.resend_window == 0 {
+            "Disabled (nations can be telegrammed again right away)".to_string()
+        } else {
+            format!("Nations telegrammed in the last {} days aren't queued again", queue.resend_window)
+        }, false
+    ).field(
+        "Region Filter", match (queue.filter.mode, queue.filter.regions.is_empty()) {
+            (FilterMode::Exclude, true) => "None".to_string(),
+            (FilterMode::Exclude, false) => format!("Excluding {}", queue.filter.regions.iter().join(", ")),
+            (FilterMode::Include, true) => "Including no regions (no nations will be queued)".to_string(),
+            (FilterMode::Include, false) => format!("Only including {}", queue.filter.regions.iter().join(", ")),
+        }, false
+    ).field(
+        "Reminders", if queue.reminders.is_empty() { "No reminders".into() } else {
+            limit_field_lines(queue.reminders.iter().enumerate().map(|(i, rule)| format!(
+                "{}. {}, {}{}", i + 1, rule.describe(), rule.channel.map_or(
+                    "no channel set (won't be sent)".into(), |channel| format!("sent in {}", channel.mention())
+                ), rule.role.map_or(String::new(), |role| format!(" pinging {}", role.mention()))
+            )).collect())
+        }, false
     ).field(
-        "Reminder Role", ping_role.map_or(
-            "None (reminders won't ping)".into(), |role| role.mention().to_string()
+        "Reminder Cooldown", format!("At least {} minutes between reminders", queue.reminder_cooldown), false
+    ).field(
+        "Quiet Hours", queue.quiet_hours.map_or(
+            "None".into(), |(start, end)| format!(
+                "{:02}:00 to {:02}:00 ({}), reminders are held back until they end", start, end, queue.timezone.name()
+            )
         ), false
     ).field(
-        "Reminder Channel", ping_channel.map_or(
-            "None (reminders won't be sent)".into(), |channel| channel.mention().to_string()
+        "Regex Filters", if queue.filter.patterns.is_empty() { "None".into() } else { queue.filter.patterns.iter().map(|v| {
+            if queue.filter.regexes.iter().any(|regex| regex.as_str() == v) {
+                format!("`{}`", v)
+            } else {
+                format!("`{}` (invalid, ignored)", v)
+            }
+        }).join("\n") }, false
+    ).field(
+        "Name Exclusions", if queue.name_rules.is_empty() { "None".into() } else {
+            queue.name_rules.iter().map(|rule| rule.description()).join("\n")
+        }, false
+    ).field(
+        "Remove Nations That Move", if queue.drop_moved {
+            "Into any region".to_string()
+        } else {
+            format!("Only into {}", prettify_name(&queue.region))
+        }, false
+    ).field(
+        "Queue Group", queue.group.as_ref().map_or(
+            "None".into(), |group| format!("`{}` (nations pulled from any queue in the group are removed from the others)", group)
         ), false
     ).field(
-        "Regex Filters", if regex_filters.is_empty() { "None".into() } else { regex_filters.iter().map(|v| format!("`{}`", v.as_str())).join("\n") }, false
+        "Shared Statistics", if queue.share_statistics {
+            "Shared with the group's other sharing queues, combined statistics are only shown to queue admins"
+        } else {
+            "Not shared, statistics only cover this queue"
+        }, false
     );
 
-    (embed, vec![
-        CreateActionRow::SelectMenu(
-            CreateSelectMenu::new("edit-queue-role", CreateSelectMenuKind::Role { default_roles: None }).placeholder(
-                "Select a reminder role"
-            )
-        ),
+    let mut components = vec![
         CreateActionRow::SelectMenu(
-            CreateSelectMenu::new("edit-queue-channel", CreateSelectMenuKind::Channel { 
-                channel_types: Some(vec![ChannelType::Text]), default_channels: None 
+            CreateSelectMenu::new("edit-queue-name-rules", CreateSelectMenuKind::String {
+                options: NameRule::ALL.iter().map(|rule| {
+                    CreateSelectMenuOption::new(rule.description(), rule.as_str())
+                        .default_selection(queue.name_rules.contains(rule))
+                }).collect()
             }).placeholder(
-                "Select a reminder channel"
-            ),
+                "Select name exclusions"
+            ).min_values(0).max_values(NameRule::ALL.len() as u8),
         ),
         CreateActionRow::Buttons(vec![
-            CreateButton::new("edit-queue-size").label("Edit Size"),
-            CreateButton::new("edit-queue-regions").label("Edit Excluded Regions"),
-            CreateButton::new("edit-queue-filter").label("Edit Filters")
+            CreateButton::new("edit-queue-size").label("Edit Limits"),
+            CreateButton::new("edit-queue-regions").label("Edit Region Filter"),
+            CreateButton::new("edit-queue-filter").label("Edit Filters"),
+            CreateButton::new("toggle-queue-drop-moved").label("Toggle Move Removal"),
+            CreateButton::new("edit-queue-group").label("Edit Group")
         ]),
         CreateActionRow::Buttons(vec![
-            CreateButton::new("edit-queue-threshold").label("Edit Threshold"),
-            CreateButton::new("delete-queue-threshold").label("Delete Threshold").style(ButtonStyle::Danger)
+            CreateButton::new("add-queue-reminder").label("Add Reminder"),
+            CreateButton::new("edit-queue-reminder-schedule").label("Edit Reminder Schedule"),
+            CreateButton::new("toggle-queue-share-statistics").label("Toggle Shared Statistics"),
         ]),
+    ];
+
+    if !queue.reminders.is_empty() {
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new("edit-queue-reminder", CreateSelectMenuKind::String {
+                options: queue.reminders.iter().enumerate().map(|(i, rule)| {
+                    CreateSelectMenuOption::new(format!("{}. {}", i + 1, rule.describe()), rule.id.to_string())
+                }).collect()
+            }).placeholder(
+                "Select a reminder to edit"
+            )
+        ));
+    }
+
+    (embed, components)
+}
+
+// Joins the lines of an embed field, leaving out those that would go over Discord's limit on field length.
+fn limit_field_lines(lines: Vec<String>) -> String {
+    let mut value = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        // Leave room to mention the lines after this one in case they don't fit
+        let reserved = if i + 1 < lines.len() {
+            format!("\n…and {} more", lines.len() - i - 1).chars().count()
+        } else {
+            0
+        };
+
+        if value.chars().count() + line.chars().count() + reserved + 1 > FIELD_VALUE_LIMIT {
+            return format!("{}\n…and {} more", value, lines.len() - i);
+        }
+
+        if !value.is_empty() {
+            value.push('\n');
+        }
+
+        value.push_str(line);
+    }
+
+    value
+}
+
+pub fn create_edit_reminder_embed(
+    queue: &Queue,
+    rule: &ReminderRule,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let embed = CreateEmbed::new().title(
+        format!("Editing Reminder: {}", prettify_name(&queue.region))
+    ).field(
+        "Fill Threshold", format!("Queue over {} nations", rule.fill_threshold), false
+    ).field(
+        "Time Threshold", format!("Last telegram over {} minutes ago", rule.time_threshold), false
+    ).field(
+        "Channel", rule.channel.map_or(
+            "None (reminder won't be sent)".into(), |channel| channel.mention().to_string()
+        ), false
+    ).field(
+        "Role", rule.role.map_or(
+            "None (reminder won't ping)".into(), |role| role.mention().to_string()
+        ), false
+    ).field(
+        "Message Preview", match rule.format_message(&queue.reminder_values()) {
+            preview if preview.is_empty() => "(empty message)".to_string(),
+            preview if preview.chars().count() > FIELD_VALUE_LIMIT => {
+                preview.chars().take(FIELD_VALUE_LIMIT - 1).collect::<String>() + "…"
+            },
+            preview => preview,
+        }, false
+    ).field(
+        "Sent As", if rule.embed { "Embed with a link to the queue, the role is pinged above it" } else { "Plain message" }, false
+    ).footer(CreateEmbedFooter::new(
+        format!("Placeholders: {}", REMINDER_PLACEHOLDERS)
+    ));
+
+    (embed, vec![
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(format!("edit-reminder-role:{}", rule.id), CreateSelectMenuKind::Role { 
+                default_roles: rule.role.map(|role| vec![role]) 
+            }).placeholder(
+                "Select a role to ping"
+            ).min_values(0)
+        ),
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(format!("edit-reminder-channel:{}", rule.id), CreateSelectMenuKind::Channel { 
+                channel_types: Some(vec![ChannelType::Text]), default_channels: rule.channel.map(|channel| vec![channel])
+            }).placeholder(
+                "Select a channel to send the reminder in"
+            ),
+        ),
         CreateActionRow::Buttons(vec![
-            CreateButton::new("clear-queue-role-channel").label("Clear Role and Channel").style(ButtonStyle::Danger)
+            CreateButton::new(format!("edit-reminder:{}", rule.id)).label("Edit Thresholds"),
+            CreateButton::new(format!("edit-reminder-message:{}", rule.id)).label("Edit Message"),
+            CreateButton::new(format!("delete-reminder:{}", rule.id)).label("Delete Reminder").style(ButtonStyle::Danger),
+            CreateButton::new("edit-queue-back").label("Back to Queue").style(ButtonStyle::Secondary),
         ]),
     ])
-}
\ No newline at end of file
+}
+
+pub fn create_reminder_embed(
+    reminder: &Reminder,
+    link: &str,
+) -> CreateEmbed {
+    CreateEmbed::new().title(
+        format!("{} Recruitment Reminder", prettify_name(&reminder.region))
+    ).description(
+        format!("{}\n\n[Go to the queue]({})", reminder.text, link)
+    ).url(link)
+}
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use serenity::all::Timestamp;
+
+// Unix timestamp of when the Akari event feed went down, or 0 while it's connected.
+static DISCONNECTED_SINCE: AtomicI64 = AtomicI64::new(0);
+
+// Amount of malformed events skipped since startup.
+static REJECTED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+// Returns whether the feed was previously disconnected.
+pub fn set_connected() -> bool {
+    DISCONNECTED_SINCE.swap(0, Ordering::Relaxed) != 0
+}
+
+// Returns whether the feed was previously connected.
+pub fn set_disconnected() -> bool {
+    DISCONNECTED_SINCE.compare_exchange(
+        0, Timestamp::now().timestamp(), Ordering::Relaxed, Ordering::Relaxed
+    ).is_ok()
+}
+
+pub fn disconnected_since() -> Option<Timestamp> {
+    match DISCONNECTED_SINCE.load(Ordering::Relaxed) {
+        0 => None,
+        time => Timestamp::from_unix_timestamp(time).ok(),
+    }
+}
+
+pub fn record_rejected_event() {
+    REJECTED_EVENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn rejected_events() -> u64 {
+    REJECTED_EVENTS.load(Ordering::Relaxed)
+}
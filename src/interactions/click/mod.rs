@@ -2,8 +2,10 @@ mod statistics;
 mod recruit;
 mod session;
 mod queue;
+mod reminder;
 
-pub use statistics::{create_statistics_menu, handle_stat_leaders_all, handle_stat_csv_all};
+pub use statistics::{create_statistics_menu, handle_stat_leaders_all, handle_stat_csv_all, statistics_scope};
 pub use recruit::handle_recruit_oneshot;
 pub use session::{handle_stream_resume, handle_stream_end};
-pub use queue::{handle_delete_queue_threshold, handle_clear_queue_role_and_channel};
\ No newline at end of file
+pub use queue::{handle_toggle_queue_drop_moved, handle_toggle_queue_share_statistics};
+pub use reminder::{handle_delete_reminder, handle_edit_queue_back};
\ No newline at end of file
@@ -3,14 +3,14 @@ use serenity::all::{ComponentInteraction, Context};
 use crate::bot::{Data, Error, util::{self, Component}};
 use crate::interactions::check_interaction_authorization;
 
-pub async fn handle_delete_queue_threshold(
+pub async fn handle_toggle_queue_drop_moved(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&component.member) {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
         util::direct_reply(ctx, Component(component), message, true).await?;
         return Ok(());
     }
-    
+
     let mut queues = data.inner.queues.lock().await;
 
     let Some(queue) = queues.get_mut(&component.channel_id) else {
@@ -21,21 +21,25 @@ pub async fn handle_delete_queue_threshold(
         return Ok(());
     };
 
-    queue.thresholds = None;
+    queue.drop_moved = !queue.drop_moved;
     queue.insert(&data.inner.pool).await;
 
     util::direct_reply(
         ctx, Component(component), 
-        "Threshold removed, use another button to refresh the edit page.", true
+        if queue.drop_moved {
+            "Nations that move to any region will now be removed from the queue, use another button to refresh the edit page."
+        } else {
+            "Only nations that move to this queue's region will now be removed from the queue, use another button to refresh the edit page."
+        }, true
     ).await?;
 
     Ok(())
 }
 
-pub async fn handle_clear_queue_role_and_channel(
+pub async fn handle_toggle_queue_share_statistics(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&component.member) {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
         util::direct_reply(ctx, Component(component), message, true).await?;
         return Ok(());
     }
@@ -50,13 +54,16 @@ pub async fn handle_clear_queue_role_and_channel(
         return Ok(());
     };
 
-    queue.ping_role = None;
-    queue.ping_channel = None;
+    queue.share_statistics = !queue.share_statistics;
     queue.insert(&data.inner.pool).await;
 
     util::direct_reply(
         ctx, Component(component), 
-        "Role and channel cleared, use another button to refresh the edit page.", true
+        if queue.share_statistics {
+            "This queue's statistics will now be shared with the other queues in its group that share theirs, use another button to refresh the edit page."
+        } else {
+            "This queue's statistics will now only cover this queue, use another button to refresh the edit page."
+        }, true
     ).await?;
 
     Ok(())
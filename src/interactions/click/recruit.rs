@@ -1,12 +1,13 @@
-use rand::seq::IndexedRandom;
 use serenity::all::{
     CacheHttp, ComponentInteraction, Context, EditInteractionResponse, UserId, Timestamp
 };
 
+use caramel::ns::format::prettify_name;
+
 use crate::api::calculate_telegram_delay;
 use crate::bot::{Data, Error, util::{self, Component}};
 use crate::embeds::create_telegram_embed;
-use crate::models::queue::QUEUE_TELEGRAM_BUFFER;
+use crate::models::queue::{Queue, QUEUE_TELEGRAM_BUFFER};
 use crate::models::report::ReportEntry;
 
 pub async fn handle_recruit_oneshot(
@@ -28,19 +29,29 @@ pub async fn handle_recruit_oneshot(
         }
     };
 
-    if data.inner.cooldowns.lock().await.contains_key(
-        &UserId::new(user_data.user_id)
-    ) {
-        util::edit_reply(ctx, Component(component), "Error: cooldown still in progress.").await?;
+    if user_data.suspended {
+        util::edit_reply(
+            ctx, Component(component), 
+            &format!("Error: {} is suspended because it left this queue's region or no longer exists! Please click 'Setup Templates' again.", prettify_name(&user_data.nation))
+        ).await?;
+
+        return Ok(());
+    }
+
+    if data.inner.cooldowns.lock().await.contains_key(&user_data.nation) {
+        util::edit_reply(
+            ctx, Component(component), 
+            &format!("Error: cooldown still in progress for {}.", prettify_name(&user_data.nation))
+        ).await?;
 
         return Ok(());
     }
 
-    let sessions = data.inner.sessions.lock().await.values().filter_map(|s| {
-        if s.queue == component.channel_id { Some(s.user) } else { None }
+    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+        (s.queue, s.user)
     }).collect::<Vec<_>>();
 
-    let ((nations, templates, update), channel) = {
+    let ((nations, templates, updates), channel) = {
         let mut queues = data.inner.queues.lock().await;
         let queue = match queues.get_mut(&component.channel_id) {
             Some(v) => v,
@@ -56,7 +67,18 @@ pub async fn handle_recruit_oneshot(
             },
         };
 
-        (queue.pull(&user_data, 8, sessions), queue.channel)
+        let batch_size = queue.batch_size_for(&user_data);
+        let channel = queue.channel;
+
+        let (nations, templates, update) = queue.pull(
+            &user_data, batch_size, sessions.iter().filter_map(|v| if v.0 == channel { Some(v.1) } else { None }).collect(), &data.inner.pool
+        ).await;
+
+        let group_updates = Queue::remove_from_group(
+            &mut queues, channel, &nations, &sessions, &data.inner.pool
+        ).await;
+
+        ((nations, templates, update.into_iter().chain(group_updates).collect::<Vec<_>>()), channel)
     };
 
     if nations.is_empty() || templates.is_empty() {
@@ -68,23 +90,22 @@ pub async fn handle_recruit_oneshot(
         return Ok(());
     }
     
-    let template = {
-        let mut rng = rand::rng();
-        templates.choose(&mut rng).expect(
-            "Template list should not be empty"
-        )
-    };
+    let template = user_data.choose_template(
+        &templates, channel, &data.inner.pool
+    ).await.expect(
+        "Template list should not be empty"
+    );
 
     let cooldown = Timestamp::now().timestamp() 
         + calculate_telegram_delay(user_data.founded) * nations.len() as i64 
         + QUEUE_TELEGRAM_BUFFER;
 
     let (embed, components) = create_telegram_embed(
-        &nations, template, &user_data.nation, cooldown, &data.inner.user_agent, false
+        &nations, &template, &user_data.nation, cooldown, &data.inner.user_agent, None
     );
 
     data.inner.cooldowns.lock().await.insert(
-        UserId::new(user_data.user_id), 
+        user_data.nation.clone(), 
         (cooldown, Some(component.clone()))
     );
 
@@ -92,7 +113,7 @@ pub async fn handle_recruit_oneshot(
         ctx.http(), EditInteractionResponse::new().embed(embed).components(components)
     ).await?;
 
-    if let Some(update) = update {
+    for update in updates {
         update.execute(ctx.clone()).await;
     }
 
@@ -105,7 +126,8 @@ pub async fn handle_recruit_oneshot(
             nation.queue_time, 
             UserId::new(user_data.user_id), 
             user_data.nation.clone(),
-            template.clone(),
+            template.code.clone(),
+            template.label.clone(),
             Timestamp::now()
         ).insert(&data.inner.pool).await;
     }
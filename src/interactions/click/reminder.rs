@@ -0,0 +1,75 @@
+use serenity::all::{ComponentInteraction, Context};
+
+use crate::bot::{Data, Error, util::{self, Component}};
+use crate::embeds::create_edit_queue_embed;
+use crate::interactions::check_interaction_authorization;
+
+pub async fn handle_delete_reminder(
+    ctx: &Context, data: &Data, component: &ComponentInteraction, key: &str
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
+        util::direct_reply(ctx, Component(component), message, true).await?;
+        return Ok(());
+    }
+
+    let Ok(id) = key.parse::<i64>() else {
+        util::direct_reply(ctx, Component(component), "Error: invalid interaction", true).await?;
+
+        return Ok(());
+    };
+
+    let mut queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get_mut(&component.channel_id) else {
+        util::direct_reply(
+            ctx, Component(component), "There is no queue set up in this channel!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let Some(index) = queue.reminders.iter().position(|rule| rule.id == id) else {
+        util::direct_reply(
+            ctx, Component(component), "This reminder no longer exists!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    queue.reminders.remove(index).remove(&data.inner.pool).await;
+
+    let (embed, components) = create_edit_queue_embed(queue);
+
+    drop(queues);
+
+    util::update_message(ctx, component, embed, components).await?;
+
+    Ok(())
+}
+
+pub async fn handle_edit_queue_back(
+    ctx: &Context, data: &Data, component: &ComponentInteraction
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
+        util::direct_reply(ctx, Component(component), message, true).await?;
+        return Ok(());
+    }
+
+    let queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get(&component.channel_id) else {
+        util::direct_reply(
+            ctx, Component(component), "There is no queue set up in this channel!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let (embed, components) = create_edit_queue_embed(queue);
+
+    drop(queues);
+
+    util::update_message(ctx, component, embed, components).await?;
+
+    Ok(())
+}
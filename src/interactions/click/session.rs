@@ -1,26 +1,34 @@
-use serenity::all::{ComponentInteraction, Context, Timestamp};
+use serenity::all::{ChannelId, ComponentInteraction, Context, Timestamp};
 use crate::bot::{Data, Error, util::{self, Component}};
 
 pub async fn handle_stream_resume(
-    ctx: &Context, data: &Data, component: &ComponentInteraction
+    ctx: &Context, data: &Data, component: &ComponentInteraction, key: &str
 ) -> Result<(), Error> {
     util::defer_ephemeral(ctx, Component(component)).await?;
 
-    let success = if let Some(session) = data.inner.sessions.lock().await.get_mut(&component.user.id) {
+    let Ok(queue) = key.parse::<ChannelId>() else {
+        util::edit_reply(ctx, Component(component), "Error: invalid interaction").await?;
+
+        return Ok(());
+    };
+
+    let session = if let Some(session) = data.inner.sessions.lock().await.get_mut(&(queue, component.user.id)) {
         session.last_activity_check = Timestamp::now();
         session.pause_time = None;
-        true
+        Some(session.clone())
     } else {
-        false
+        None
     };
     
-    if success {
+    if let Some(session) = session {
+        session.insert(&data.inner.pool).await;
+
         util::edit_reply(
             ctx, Component(component), "Session resumed!"
         ).await?;
     } else {
         util::edit_reply(
-            ctx, Component(component), "Error: You do not currently have a session in progress."
+            ctx, Component(component), "Error: You do not currently have a session in progress for this queue."
         ).await?;
     }
 
@@ -28,18 +36,27 @@ pub async fn handle_stream_resume(
 }
 
 pub async fn handle_stream_end(
-    ctx: &Context, data: &Data, component: &ComponentInteraction
+    ctx: &Context, data: &Data, component: &ComponentInteraction, key: &str
 ) -> Result<(), Error> {
     util::defer_ephemeral(ctx, Component(component)).await?;
-    let success = data.inner.sessions.lock().await.remove(&component.user.id).is_some();
+
+    let Ok(queue) = key.parse::<ChannelId>() else {
+        util::edit_reply(ctx, Component(component), "Error: invalid interaction").await?;
+
+        return Ok(());
+    };
+
+    let session = data.inner.sessions.lock().await.remove(&(queue, component.user.id));
     
-    if success {
+    if let Some(session) = session {
+        session.remove(&data.inner.pool).await;
+
         util::edit_reply(
             ctx, Component(component), "Your current session has been stopped!"
         ).await?;
     } else {
         util::edit_reply(
-            ctx, Component(component), "Error: You do not currently have a session in progress."
+            ctx, Component(component), "Error: You do not currently have a session in progress for this queue."
         ).await?;
     }
 
@@ -1,17 +1,53 @@
 use itertools::Itertools;
 use serenity::all::{
-    CacheHttp, ComponentInteraction, Context, CreateAttachment, CreateInteractionResponse, 
-    CreateInteractionResponseMessage, EditInteractionResponse
+    CacheHttp, ChannelId, ComponentInteraction, Context, CreateAttachment, CreateInteractionResponse, 
+    CreateInteractionResponseMessage, EditInteractionResponse, Member
 };
 
 use crate::bot::{Data, Error, util::{self, Component}};
 use crate::embeds::create_statistics_embed;
-use crate::models::report::ReportEntry;
+use crate::interactions::check_interaction_authorization;
+use crate::models::{queue::Queue, report::ReportEntry};
+
+// The channels whose reports the member can see from the queue's channel, along with those of them
+// that belong to another server. Only queue admins see the statistics shared by the rest of the group.
+pub async fn statistics_scope(
+    ctx: &Context, data: &Data, channel: ChannelId, member: &Option<Member>
+) -> Option<(Vec<ChannelId>, Vec<ChannelId>)> {
+    let channels = Queue::statistics_channels(&*data.inner.queues.lock().await, channel)?;
+
+    if channels.len() == 1 || check_interaction_authorization(data, member).await.is_some() {
+        return Some((vec![channel], Vec::new()));
+    }
+
+    let guild = member.as_ref().map(|v| v.guild_id);
+
+    let mut foreign = Vec::new();
+    for queue in &channels {
+        let queue_guild = queue.to_channel(ctx).await.ok().and_then(|v| v.guild()).map(|v| v.guild_id);
+
+        if queue_guild.is_none() || queue_guild != guild {
+            foreign.push(*queue);
+        }
+    }
+
+    Some((channels, foreign))
+}
 
 pub async fn create_statistics_menu(
-    ctx: &Context, _: &Data, component: &ComponentInteraction
+    ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    let (embed, components) = create_statistics_embed();
+    let statistics = data.inner.queues.lock().await.get(&component.channel_id).map(create_statistics_embed);
+
+    let Some((embed, components)) = statistics else {
+        util::direct_reply(
+            ctx, Component(component), 
+            "Invalid interaction: no queue linked to channel", 
+            true
+        ).await?;
+
+        return Ok(());
+    };
 
     Ok(component.create_response(ctx.http(), CreateInteractionResponse::Message(
         CreateInteractionResponseMessage::new().embed(embed).components(components).ephemeral(true)
@@ -21,20 +57,22 @@ pub async fn create_statistics_menu(
 pub async fn handle_stat_leaders_all(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    if !data.inner.queues.lock().await.contains_key(&component.channel_id) {
+    let scope = statistics_scope(ctx, data, component.channel_id, &component.member).await;
+
+    let Some((channels, _)) = scope else {
         util::direct_reply(
-            ctx, Component(&component), 
+            ctx, Component(component), 
             "Invalid interaction: no queue linked to channel", 
             true
         ).await?;
 
         return Ok(());
-    }
+    };
 
     util::defer_ephemeral(ctx, Component(component)).await?;
 
     let leaders = ReportEntry::count(
-        &data.inner.pool, component.channel_id, None
+        &data.inner.pool, &channels, None
     ).await?;
 
     if leaders.is_empty() {
@@ -55,38 +93,27 @@ pub async fn handle_stat_leaders_all(
 pub async fn handle_stat_csv_all(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    if !data.inner.queues.lock().await.contains_key(&component.channel_id) {
+    let scope = statistics_scope(ctx, data, component.channel_id, &component.member).await;
+
+    let Some((channels, foreign)) = scope else {
         util::direct_reply(
             ctx, Component(component),
             "Invalid interaction: no queue linked to channel", true
         ).await?;
 
         return Ok(());
-    }
+    };
 
     util::defer_ephemeral(ctx, Component(component)).await?;
 
     let entries = ReportEntry::query(
-        &data.inner.pool, component.channel_id, None
+        &data.inner.pool, &channels, None
     ).await?;
 
     if entries.is_empty() {
         util::edit_reply(ctx, Component(component), "Error: no results recorded!").await?;
     } else {
-        let mut output: Vec<u8> = Vec::new();
-        let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(&mut output);
-
-        writer.write_record(&[
-            "Nation Name", "Event Type", "Event Source", "Queued at Time", 
-            "Recruiter Discord ID", "Sender Nation", "Telegram Template",
-            "Sent at Time", "Moved to Region?", "Moved at Time"
-        ])?;
-
-        for entry in entries {
-            writer.serialize(entry)?;
-        }
-
-        drop(writer);
+        let output = ReportEntry::write_csv(entries, &foreign)?;
 
         component.edit_response(
 ctx.http(), EditInteractionResponse::new().content(
@@ -1,12 +1,61 @@
 use serenity::all::{ComponentInteraction, ComponentInteractionDataKind, Context};
 
 use crate::bot::{Data, Error, util::{self, Component}};
+use crate::embeds::create_edit_reminder_embed;
 use crate::interactions::check_interaction_authorization;
+use crate::models::queue::NameRule;
 
-pub async fn handle_edit_queue_role(
+pub async fn handle_edit_queue_reminder(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&component.member) {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
+        util::direct_reply(ctx, Component(component), message, true).await?;
+        return Ok(());
+    }
+
+    let id = {
+        let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+            util::direct_reply(
+                ctx, Component(component), "Error: invalid interaction", true
+            ).await?;
+        
+            return Ok(());
+        };
+
+        values.first().and_then(|v| v.parse::<i64>().ok())
+    };
+
+    let queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get(&component.channel_id) else {
+        util::direct_reply(
+            ctx, Component(component), "There is no queue set up in this channel!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let Some(rule) = queue.reminders.iter().find(|rule| Some(rule.id) == id) else {
+        util::direct_reply(
+            ctx, Component(component), "This reminder no longer exists!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let (embed, components) = create_edit_reminder_embed(queue, rule);
+
+    drop(queues);
+
+    util::update_message(ctx, component, embed, components).await?;
+
+    Ok(())
+}
+
+pub async fn handle_edit_reminder_role(
+    ctx: &Context, data: &Data, component: &ComponentInteraction, key: &str
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
         util::direct_reply(ctx, Component(component), message, true).await?;
         return Ok(());
     }
@@ -33,21 +82,31 @@ pub async fn handle_edit_queue_role(
         return Ok(());
     };
 
-    queue.ping_role = role;
-    queue.insert(&data.inner.pool).await;
+    let Some(rule) = queue.reminders.iter_mut().find(|rule| key.parse::<i64>() == Ok(rule.id)) else {
+        util::direct_reply(
+            ctx, Component(component), "This reminder no longer exists!", true
+        ).await?;
+        
+        return Ok(());
+    };
 
-    util::direct_reply(
-        ctx, Component(component), 
-        "Role edited, use another button to refresh the edit page.", true
-    ).await?;
+    rule.role = role;
+    rule.insert(&data.inner.pool).await;
+
+    let rule = rule.clone();
+    let (embed, components) = create_edit_reminder_embed(queue, &rule);
+
+    drop(queues);
+
+    util::update_message(ctx, component, embed, components).await?;
 
     Ok(())
 }
 
-pub async fn handle_edit_queue_channel(
-    ctx: &Context, data: &Data, component: &ComponentInteraction
+pub async fn handle_edit_reminder_channel(
+    ctx: &Context, data: &Data, component: &ComponentInteraction, key: &str
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&component.member) {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
         util::direct_reply(ctx, Component(component), message, true).await?;
         return Ok(());
     }
@@ -74,13 +133,64 @@ pub async fn handle_edit_queue_channel(
         return Ok(());
     };
 
-    queue.ping_channel = channel;
+    let Some(rule) = queue.reminders.iter_mut().find(|rule| key.parse::<i64>() == Ok(rule.id)) else {
+        util::direct_reply(
+            ctx, Component(component), "This reminder no longer exists!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    rule.channel = channel;
+    rule.insert(&data.inner.pool).await;
+
+    let rule = rule.clone();
+    let (embed, components) = create_edit_reminder_embed(queue, &rule);
+
+    drop(queues);
+
+    util::update_message(ctx, component, embed, components).await?;
+
+    Ok(())
+}
+
+pub async fn handle_edit_queue_name_rules(
+    ctx: &Context, data: &Data, component: &ComponentInteraction
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &component.member).await {
+        util::direct_reply(ctx, Component(component), message, true).await?;
+        return Ok(());
+    }
+
+    let rules = {
+        let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+            util::direct_reply(
+                ctx, Component(component), "Error: invalid interaction", true
+            ).await?;
+        
+            return Ok(());
+        };
+
+        values.iter().filter_map(|v| NameRule::parse(v)).collect::<Vec<_>>()
+    };
+
+    let mut queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get_mut(&component.channel_id) else {
+        util::direct_reply(
+            ctx, Component(component), "There is no queue set up in this channel!", true
+        ).await?;
+        
+        return Ok(());
+    };
+
+    queue.name_rules = rules;
     queue.insert(&data.inner.pool).await;
 
     util::direct_reply(
         ctx, Component(component), 
-        "Role edited, use another button to refresh the edit page.", true
+        "Name exclusions edited, use another button to refresh the edit page.", true
     ).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -2,11 +2,13 @@ mod statistics;
 mod session;
 mod setup;
 mod queue;
+mod reminder;
 
 pub use statistics::{spawn_stat_time_form, process_stat_leaders_custom_form, process_stat_csv_custom_form};
 pub use session::{spawn_session_form, process_session_form};
 pub use setup::{spawn_setup_form, process_setup_form};
 pub use queue::{
-    spawn_queue_size_form, spawn_queue_regions_form, spawn_queue_threshold_form, spawn_queue_filter_form,
-    process_queue_size_form, process_queue_regions_form, process_queue_threshold_form, process_queue_filter_form
-};
\ No newline at end of file
+    spawn_queue_size_form, spawn_queue_regions_form, spawn_queue_reminder_schedule_form, spawn_queue_filter_form, spawn_queue_group_form,
+    process_queue_size_form, process_queue_regions_form, process_queue_reminder_schedule_form, process_queue_filter_form, process_queue_group_form
+};
+pub use reminder::{spawn_reminder_form, spawn_reminder_message_form, process_reminder_form, process_reminder_message_form};
@@ -1,6 +1,8 @@
+use itertools::Itertools;
 use log::warn;
 use regex::Regex;
 use uuid::Uuid;
+use chrono_tz::Tz;
 use serenity::all::{
     CacheHttp, ComponentInteraction, Context, CreateActionRow, CreateInputText, CreateInteractionResponse, 
     CreateModal, InputTextStyle, ModalInteraction, ActionRowComponent, EditInteractionResponse
@@ -10,6 +12,10 @@ use caramel::ns::format::canonicalize_name;
 
 use crate::{bot::{Data, Error, util::{self, Modal}}, embeds::create_edit_queue_embed};
 use crate::interactions::check_interaction_authorization;
+use crate::models::queue::{
+    parse_quiet_hours, region_group, FilterMode, DEFAULT_REMINDER_COOLDOWN, MAX_BATCH_SIZE, MAX_REMINDER_COOLDOWN, 
+    MAX_RESEND_WINDOW, MIN_REMINDER_COOLDOWN, REGION_GROUPS
+};
 
 pub async fn spawn_queue_size_form(
     ctx: &Context, data: &Data, component: &ComponentInteraction
@@ -17,12 +23,25 @@ pub async fn spawn_queue_size_form(
     let key = Uuid::new_v4().to_string();
     data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
 
+    let (size, batch_size, resend_window) = data.inner.queues.lock().await.get(&component.channel_id).map_or(
+        (String::new(), String::new(), String::new()), 
+        |v| (v.size.to_string(), v.batch_size.to_string(), v.resend_window.to_string())
+    );
+
     component.create_response(ctx.http(), CreateInteractionResponse::Modal(
-        CreateModal::new(format!("queue-size-modal:{}", key), "Edit Queue Size").components(
+        CreateModal::new(format!("queue-size-modal:{}", key), "Edit Queue Limits").components(
             vec![CreateActionRow::InputText(
                 CreateInputText::new(
                     InputTextStyle::Short, "Queue Size", "queue-size"
-                ).placeholder("Size must be between 50 and 500")
+                ).placeholder("Size must be between 50 and 500").value(size)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Batch Size", "queue-batch-size"
+                ).placeholder(format!("Nations per telegram, between 1 and {}", MAX_BATCH_SIZE)).value(batch_size)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Resend Window (in days)", "queue-resend-window"
+                ).placeholder(format!("Skip nations telegrammed this recently, 0 to {} (0 disables)", MAX_RESEND_WINDOW)).value(resend_window)
             )]
         )
     )).await?;
@@ -36,12 +55,23 @@ pub async fn spawn_queue_regions_form(
     let key = Uuid::new_v4().to_string();
     data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
 
+    let (mode, regions) = data.inner.queues.lock().await.get(&component.channel_id).map_or(
+        (FilterMode::default().as_str().to_string(), String::new()), 
+        |v| (v.filter.mode.as_str().to_string(), v.filter.regions.join("\n"))
+    );
+
     component.create_response(ctx.http(), CreateInteractionResponse::Modal(
-        CreateModal::new(format!("queue-regions-modal:{}", key), "Edit Excluded Regions").components(
+        CreateModal::new(format!("queue-regions-modal:{}", key), "Edit Region Filter").components(
             vec![CreateActionRow::InputText(
                 CreateInputText::new(
-                    InputTextStyle::Paragraph, "Regions to Exclude", "regions"
-                ).placeholder("One region per line, leave empty to clear the list").required(false)
+                    InputTextStyle::Short, "Mode", "regions-mode"
+                ).placeholder("'exclude' to skip the listed regions, 'include' to only queue from them").value(mode)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Paragraph, "Regions", "regions"
+                ).placeholder(format!(
+                    "One region per line, or a group: {}", REGION_GROUPS.iter().map(|(group, _)| format!("@{}", group)).join(", ")
+                )).value(regions).required(false)
             )]
         )
     )).await?;
@@ -49,22 +79,37 @@ pub async fn spawn_queue_regions_form(
     Ok(())
 }
 
-pub async fn spawn_queue_threshold_form(
+pub async fn spawn_queue_reminder_schedule_form(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
     let key = Uuid::new_v4().to_string();
     data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
 
+    let (cooldown, quiet_hours, timezone) = data.inner.queues.lock().await.get(&component.channel_id).map_or(
+        (DEFAULT_REMINDER_COOLDOWN.to_string(), String::new(), Tz::UTC.name().to_string()), 
+        |v| (
+            v.reminder_cooldown.to_string(), 
+            v.quiet_hours.map_or(String::new(), |(start, end)| format!("{}-{}", start, end)), 
+            v.timezone.name().to_string()
+        )
+    );
+
     component.create_response(ctx.http(), CreateInteractionResponse::Modal(
-        CreateModal::new(format!("queue-threshold-modal:{}", key), "Edit/Set Reminder Threshold").components(
+        CreateModal::new(format!("queue-reminder-schedule-modal:{}", key), "Edit Reminder Schedule").components(
             vec![CreateActionRow::InputText(
                 CreateInputText::new(
-                    InputTextStyle::Short, "Fill Threshold", "fill-threshold"
-                ).placeholder("Minimum amount of nations to trigger a reminder at")
+                    InputTextStyle::Short, "Reminder Cooldown (in minutes)", "reminder-cooldown"
+                ).placeholder(format!(
+                    "Minimum time between reminders, {}-{}", MIN_REMINDER_COOLDOWN, MAX_REMINDER_COOLDOWN
+                )).value(cooldown)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Quiet Hours", "quiet-hours"
+                ).placeholder("Hours to hold back reminders, e.g. 22-8 (leave empty for none)").value(quiet_hours).required(false)
             ), CreateActionRow::InputText(
                 CreateInputText::new(
-                    InputTextStyle::Short, "Time Threshold (in minutes)", "time-threshold"
-                ).placeholder("Minimum time since a telegram was sent to trigger at")
+                    InputTextStyle::Short, "Time Zone", "timezone"
+                ).placeholder("Time zone for quiet hours, e.g. Europe/London").value(timezone).required(false)
             )]
         )
     )).await?;
@@ -78,12 +123,39 @@ pub async fn spawn_queue_filter_form(
     let key = Uuid::new_v4().to_string();
     data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
 
+    let patterns = data.inner.queues.lock().await.get(&component.channel_id).map_or(
+        String::new(), |v| v.filter.patterns.join("\n")
+    );
+
     component.create_response(ctx.http(), CreateInteractionResponse::Modal(
         CreateModal::new(format!("queue-filter-modal:{}", key), "Edit Filters").components(
             vec![CreateActionRow::InputText(
                 CreateInputText::new(
                     InputTextStyle::Paragraph, "Regex Filters", "filters"
-                ).placeholder("One pattern per line, leave empty to clear the list").required(false)
+                ).placeholder("One pattern per line, leave empty to clear the list").value(patterns).required(false)
+            )]
+        )
+    )).await?;
+
+    Ok(())
+}
+
+pub async fn spawn_queue_group_form(
+    ctx: &Context, data: &Data, component: &ComponentInteraction
+) -> Result<(), Error> {
+    let key = Uuid::new_v4().to_string();
+    data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
+
+    let group = data.inner.queues.lock().await.get(&component.channel_id).and_then(
+        |v| v.group.clone()
+    ).unwrap_or_default();
+
+    component.create_response(ctx.http(), CreateInteractionResponse::Modal(
+        CreateModal::new(format!("queue-group-modal:{}", key), "Edit Queue Group").components(
+            vec![CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Group Code", "group"
+                ).placeholder("'new' to create a group, another queue's code to join it, empty to leave").value(group).required(false)
             )]
         )
     )).await?;
@@ -94,7 +166,7 @@ pub async fn spawn_queue_filter_form(
 pub async fn process_queue_size_form(
      ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&modal.member) {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
         util::direct_reply(ctx, Modal(modal), message, true).await?;
         return Ok(());
     }
@@ -111,12 +183,16 @@ pub async fn process_queue_size_form(
     };
 
     let mut size = None;
+    let mut batch_size = None;
+    let mut resend_window = None;
 
     for row in components {
         for component in &row.components {
             if let ActionRowComponent::InputText(input) = component {
                 match input.custom_id.as_str() {
                     "queue-size" => size = input.value.clone(),
+                    "queue-batch-size" => batch_size = input.value.clone(),
+                    "queue-resend-window" => resend_window = input.value.clone(),
                     _ => {}
                 }
             }
@@ -124,6 +200,8 @@ pub async fn process_queue_size_form(
     }
 
     let size = size.and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let batch_size = batch_size.and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let resend_window = resend_window.and_then(|v| v.trim().parse::<u64>().ok());
 
     if size < 50 || size > 500 {
         util::edit_reply(
@@ -133,6 +211,22 @@ pub async fn process_queue_size_form(
         return Ok(());
     }
 
+    if batch_size < 1 || batch_size > MAX_BATCH_SIZE as i64 {
+        util::edit_reply(
+            ctx, Modal(modal), &format!("Error: batch size is not a number or doesn't fit in range 1-{}", MAX_BATCH_SIZE)
+        ).await?;
+        
+        return Ok(());
+    }
+
+    let Some(resend_window) = resend_window.filter(|v| *v <= MAX_RESEND_WINDOW) else {
+        util::edit_reply(
+            ctx, Modal(modal), &format!("Error: resend window is not a number or doesn't fit in range 0-{}", MAX_RESEND_WINDOW)
+        ).await?;
+        
+        return Ok(());
+    };
+
     let mut queues = data.inner.queues.lock().await;
 
     let Some(queue) = queues.get_mut(&modal.channel_id) else {
@@ -144,11 +238,11 @@ pub async fn process_queue_size_form(
     };
 
     queue.size = size as usize;
+    queue.batch_size = batch_size as usize;
+    queue.resend_window = resend_window;
     queue.insert(&data.inner.pool).await;
 
-    let (embed, components) = create_edit_queue_embed(
-        &queue.region, queue.size, &queue.filter.regions, &queue.thresholds, &queue.ping_channel, &queue.ping_role, &queue.filter.regexes
-    );
+    let (embed, components) = create_edit_queue_embed(queue);
 
     if let Err(err) = ctx.http().edit_original_interaction_response(
         &token, 
@@ -166,7 +260,7 @@ pub async fn process_queue_size_form(
 pub async fn process_queue_regions_form(
      ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&modal.member) {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
         util::direct_reply(ctx, Modal(modal), message, true).await?;
         return Ok(());
     }
@@ -182,12 +276,14 @@ pub async fn process_queue_regions_form(
         return Ok(());
     };
 
+    let mut mode = None;
     let mut regions = None;
 
     for row in components {
         for component in &row.components {
             if let ActionRowComponent::InputText(input) = component {
                 match input.custom_id.as_str() {
+                    "regions-mode" => mode = input.value.clone(),
                     "regions" => regions = input.value.clone(),
                     _ => {}
                 }
@@ -195,6 +291,38 @@ pub async fn process_queue_regions_form(
         }
     }
 
+    let Some(mode) = mode.and_then(|v| FilterMode::parse(&v.trim().to_lowercase())) else {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: mode must be either 'exclude' or 'include'"
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let regions = regions.map(|v| v.split("\n").map(|s| match s.trim().strip_prefix('@') {
+        Some(group) => format!("@{}", group.trim().to_lowercase()),
+        None => canonicalize_name(s.trim()),
+    }).filter(|s| !s.is_empty()).collect::<Vec<_>>()).unwrap_or_default();
+
+    if mode == FilterMode::Include && regions.is_empty() {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: include mode needs at least one region, otherwise no nations would be queued"
+        ).await?;
+
+        return Ok(());
+    }
+
+    if let Some(group) = regions.iter().find(|v| v.strip_prefix('@').is_some_and(|group| region_group(group).is_none())) {
+        util::edit_reply(
+            ctx, Modal(modal), &format!(
+                "Error: unknown region group {}, available groups are {}", group,
+                REGION_GROUPS.iter().map(|(group, _)| format!("@{}", group)).join(", ")
+            )
+        ).await?;
+        
+        return Ok(());
+    }
+
     let mut queues = data.inner.queues.lock().await;
 
     let Some(queue) = queues.get_mut(&modal.channel_id) else {
@@ -205,15 +333,12 @@ pub async fn process_queue_regions_form(
         return Ok(());
     };
 
-    queue.filter.regions = regions.and_then(|v| 
-        Some(v.split("\n").map(|s| canonicalize_name(&s.trim())).filter(|s| !s.is_empty()).collect())
-    ).unwrap_or(vec![]);
+    queue.filter.mode = mode;
+    queue.filter.regions = regions;
 
     queue.insert(&data.inner.pool).await;
 
-    let (embed, components) = create_edit_queue_embed(
-        &queue.region, queue.size, &queue.filter.regions, &queue.thresholds, &queue.ping_channel, &queue.ping_role, &queue.filter.regexes
-    );
+    let (embed, components) = create_edit_queue_embed(queue);
 
     if let Err(err) = ctx.http().edit_original_interaction_response(
         &token, 
@@ -231,7 +356,7 @@ pub async fn process_queue_regions_form(
 pub async fn process_queue_filter_form(
      ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&modal.member) {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
         util::direct_reply(ctx, Modal(modal), message, true).await?;
         return Ok(());
     }
@@ -270,15 +395,158 @@ pub async fn process_queue_filter_form(
         return Ok(());
     };
 
-    queue.filter.regexes = filters.map(|v| 
-        v.split("\n").map(|s| s.trim()).filter(|s| !s.is_empty()).filter_map(|s| Regex::new(&s).ok()).collect()
+    let patterns = filters.map(|v| 
+        v.split("\n").map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>()
     ).unwrap_or_default();
 
+    let mut regexes = Vec::new();
+    let mut errors = Vec::new();
+
+    for pattern in &patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => regexes.push(regex),
+            Err(err) => errors.push(format!("`{}`:\n```\n{}\n```", pattern, err)),
+        }
+    }
+
+    if !errors.is_empty() {
+        drop(queues);
+
+        util::edit_reply(
+            ctx, Modal(modal), &truncate_reply(format!("Error: filters weren't saved, some patterns are invalid:\n{}", errors.join("\n")))
+        ).await?;
+
+        return Ok(());
+    }
+
+    // Nations already in the queue aren't removed, but admins can see what each pattern catches
+    let preview = regexes.iter().map(|regex| {
+        let matches = queue.nation_names().filter(|v| regex.is_match(v)).collect::<Vec<_>>();
+
+        match matches.len() {
+            0 => format!("`{}`: no nations currently in the queue", regex.as_str()),
+            len if len > FILTER_PREVIEW_LENGTH => format!(
+                "`{}`: {} and {} more", regex.as_str(), matches[..FILTER_PREVIEW_LENGTH].join(", "), len - FILTER_PREVIEW_LENGTH
+            ),
+            _ => format!("`{}`: {}", regex.as_str(), matches.join(", ")),
+        }
+    }).collect::<Vec<_>>();
+
+    queue.filter.patterns = patterns;
+    queue.filter.regexes = regexes;
     queue.insert(&data.inner.pool).await;
 
-    let (embed, components) = create_edit_queue_embed(
-        &queue.region, queue.size, &queue.filter.regions, &queue.thresholds, &queue.ping_channel, &queue.ping_role, &queue.filter.regexes
-    );
+    let (embed, components) = create_edit_queue_embed(queue);
+    drop(queues);
+
+    if let Err(err) = ctx.http().edit_original_interaction_response(
+        &token, 
+        &EditInteractionResponse::new().embed(embed).components(components), 
+        vec![]
+    ).await {
+        warn!("Error while editing interaction message: {err}");
+    }
+
+    if preview.is_empty() {
+        modal.delete_response(ctx.http()).await?;
+    } else {
+        util::edit_reply(
+            ctx, Modal(modal), &truncate_reply(format!("Filters saved. Nations currently in the queue matching each pattern:\n{}", preview.join("\n")))
+        ).await?;
+    }
+
+    Ok(())
+}
+
+const FILTER_PREVIEW_LENGTH: usize = 10;
+const MAX_REPLY_LENGTH: usize = 2000; // Discord's message length limit
+
+fn truncate_reply(text: String) -> String {
+    if text.chars().count() <= MAX_REPLY_LENGTH {
+        return text;
+    }
+
+    let mut text = text.chars().take(MAX_REPLY_LENGTH - 3).collect::<String>();
+    text.push_str("...");
+    text
+}
+
+pub async fn process_queue_group_form(
+     ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
+        util::direct_reply(ctx, Modal(modal), message, true).await?;
+        return Ok(());
+    }
+
+    let components = &modal.data.components;
+    util::defer_ephemeral(ctx, Modal(modal)).await?;
+
+    let Some(token) = data.inner.interaction_tokens.lock().await.remove(key) else {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: invalid interaction"
+        ).await?;
+
+        return Ok(());
+    };
+
+    let mut group = None;
+
+    for row in components {
+        for component in &row.components {
+            if let ActionRowComponent::InputText(input) = component && input.custom_id == "group" {
+                group = input.value.clone();
+            }
+        }
+    }
+
+    let mut queues = data.inner.queues.lock().await;
+
+    let Some(region) = queues.get(&modal.channel_id).map(|v| v.region.clone()) else {
+        util::edit_reply(
+            ctx, Modal(modal), "There is no queue set up in this channel!"
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let group = match group.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+        None => None,
+        Some(v) if v.eq_ignore_ascii_case("new") => Some(Uuid::new_v4().to_string()),
+        Some(v) => {
+            let members = queues.values().filter(
+                |queue| queue.channel != modal.channel_id && queue.group.as_ref() == Some(&v)
+            ).collect::<Vec<_>>();
+
+            if members.is_empty() {
+                util::edit_reply(
+                    ctx, Modal(modal), "Error: no other queue uses this group code"
+                ).await?;
+
+                return Ok(());
+            }
+
+            // Grouped queues hand out the same nations, so they have to recruit for the same region
+            if members.iter().any(|queue| queue.region != region) {
+                util::edit_reply(
+                    ctx, Modal(modal), "Error: queues in a group must recruit for the same region"
+                ).await?;
+
+                return Ok(());
+            }
+
+            Some(v)
+        }
+    };
+
+    let Some(queue) = queues.get_mut(&modal.channel_id) else {
+        return Ok(());
+    };
+
+    queue.group = group;
+    queue.insert(&data.inner.pool).await;
+
+    let (embed, components) = create_edit_queue_embed(queue);
 
     if let Err(err) = ctx.http().edit_original_interaction_response(
         &token, 
@@ -293,10 +561,10 @@ pub async fn process_queue_filter_form(
     Ok(())
 }
 
-pub async fn process_queue_threshold_form(
+pub async fn process_queue_reminder_schedule_form(
      ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
 ) -> Result<(), Error> {
-    if let Some(message) = check_interaction_authorization(&modal.member) {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
         util::direct_reply(ctx, Modal(modal), message, true).await?;
         return Ok(());
     }
@@ -312,15 +580,17 @@ pub async fn process_queue_threshold_form(
         return Ok(());
     };
 
-    let mut fill_threshold = None;
-    let mut time_threshold = None;
+    let mut cooldown = None;
+    let mut quiet_hours = None;
+    let mut timezone = None;
 
     for row in components {
         for component in &row.components {
             if let ActionRowComponent::InputText(input) = component {
                 match input.custom_id.as_str() {
-                    "fill-threshold" => fill_threshold = input.value.clone(),
-                    "time-threshold" => time_threshold = input.value.clone(),
+                    "reminder-cooldown" => cooldown = input.value.clone(),
+                    "quiet-hours" => quiet_hours = input.value.clone(),
+                    "timezone" => timezone = input.value.clone(),
                     _ => {}
                 }
             }
@@ -337,32 +607,55 @@ pub async fn process_queue_threshold_form(
         return Ok(());
     };
 
-    let fill_threshold = fill_threshold.and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
-    let time_threshold = time_threshold.and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    let cooldown = cooldown.and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
 
-    if fill_threshold < 30 || fill_threshold > 500 {
+    if !(MIN_REMINDER_COOLDOWN..=MAX_REMINDER_COOLDOWN).contains(&cooldown) {
         util::edit_reply(
-            ctx, Modal(modal), "Error: fill threshold is not a number or doesn't fit in range 30-500"
+            ctx, Modal(modal), &format!(
+                "Error: reminder cooldown is not a number or doesn't fit in range {}-{}", MIN_REMINDER_COOLDOWN, MAX_REMINDER_COOLDOWN
+            )
         ).await?;
         
         return Ok(());
     }
 
-    if time_threshold < 30 || time_threshold > 360 {
-        util::edit_reply(
-            ctx, Modal(modal), "Error: time threshold is not a number or doesn't fit in range 30-360"
-        ).await?;
-        
-        return Ok(());
-    }
+    let quiet_hours = match quiet_hours.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => {
+            let Some(quiet_hours) = parse_quiet_hours(value) else {
+                util::edit_reply(
+                    ctx, Modal(modal), "Error: quiet hours must be two different hours between 0 and 23, e.g. 22-8"
+                ).await?;
+                
+                return Ok(());
+            };
+
+            Some(quiet_hours)
+        },
+        None => None,
+    };
 
-    queue.thresholds = Some((fill_threshold, time_threshold));
+    let timezone = match timezone.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => {
+            let Ok(timezone) = value.parse::<Tz>() else {
+                util::edit_reply(
+                    ctx, Modal(modal), &format!("Error: unknown time zone '{}', use a name such as Europe/London", value)
+                ).await?;
+                
+                return Ok(());
+            };
+
+            timezone
+        },
+        None => Tz::UTC,
+    };
+
+    queue.reminder_cooldown = cooldown;
+    queue.quiet_hours = quiet_hours;
+    queue.timezone = timezone;
 
     queue.insert(&data.inner.pool).await;
 
-    let (embed, components) = create_edit_queue_embed(
-        &queue.region, queue.size, &queue.filter.regions, &queue.thresholds, &queue.ping_channel, &queue.ping_role, &queue.filter.regexes
-    );
+    let (embed, components) = create_edit_queue_embed(queue);
 
     if let Err(err) = ctx.http().edit_original_interaction_response(
         &token, 
@@ -0,0 +1,321 @@
+use log::warn;
+use uuid::Uuid;
+use serenity::all::{
+    CacheHttp, ComponentInteraction, Context, CreateActionRow, CreateInputText, CreateInteractionResponse, 
+    CreateModal, InputTextStyle, ModalInteraction, ActionRowComponent, EditInteractionResponse
+};
+
+use crate::{bot::{Data, Error, util::{self, Component, Modal}}, embeds::create_edit_reminder_embed};
+use crate::interactions::check_interaction_authorization;
+use crate::models::reminder::{
+    ReminderRule, MAX_FILL_THRESHOLD, MAX_REMINDER_MESSAGE_LENGTH, MAX_REMINDER_RULES, MAX_TIME_THRESHOLD, MIN_FILL_THRESHOLD, MIN_TIME_THRESHOLD
+};
+
+// Opens the form for a new reminder, or for the reminder with the given id if there is one.
+pub async fn spawn_reminder_form(
+    ctx: &Context, data: &Data, component: &ComponentInteraction, rule: Option<&str>
+) -> Result<(), Error> {
+    let key = Uuid::new_v4().to_string();
+    data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
+
+    let existing = match rule.and_then(|v| v.parse::<i64>().ok()) {
+        Some(id) => data.inner.queues.lock().await.get(&component.channel_id).and_then(|queue| {
+            queue.reminders.iter().find(|rule| rule.id == id).cloned()
+        }),
+        None => None,
+    };
+
+    let (custom_id, title) = match &existing {
+        Some(rule) => (format!("reminder-modal:{}:{}", key, rule.id), "Edit Reminder"),
+        None => (format!("reminder-modal:{}", key), "Add Reminder"),
+    };
+
+    let (fill_threshold, time_threshold) = existing.map_or(
+        (String::new(), String::new()),
+        |v| (v.fill_threshold.to_string(), v.time_threshold.to_string())
+    );
+
+    component.create_response(ctx.http(), CreateInteractionResponse::Modal(
+        CreateModal::new(custom_id, title).components(
+            vec![CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Fill Threshold", "fill-threshold"
+                ).placeholder("Minimum amount of nations to trigger a reminder at").value(fill_threshold)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Time Threshold (in minutes)", "time-threshold"
+                ).placeholder("Minimum time since a telegram was sent to trigger at").value(time_threshold)
+            )]
+        )
+    )).await?;
+
+    Ok(())
+}
+
+pub async fn process_reminder_form(
+     ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
+        util::direct_reply(ctx, Modal(modal), message, true).await?;
+        return Ok(());
+    }
+    
+    let components = &modal.data.components;
+    util::defer_ephemeral(ctx, Modal(modal)).await?;
+
+    let (key, id) = match key.split_once(':') {
+        Some((key, id)) => (key, id.parse::<i64>().ok()),
+        None => (key, None),
+    };
+
+    let Some(token) = data.inner.interaction_tokens.lock().await.remove(key) else {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: invalid interaction"
+        ).await?;
+
+        return Ok(());
+    };
+
+    let mut fill_threshold = None;
+    let mut time_threshold = None;
+
+    for row in components {
+        for component in &row.components {
+            if let ActionRowComponent::InputText(input) = component {
+                match input.custom_id.as_str() {
+                    "fill-threshold" => fill_threshold = input.value.clone(),
+                    "time-threshold" => time_threshold = input.value.clone(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let fill_threshold = fill_threshold.and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+    let time_threshold = time_threshold.and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+
+    if !(MIN_FILL_THRESHOLD..=MAX_FILL_THRESHOLD).contains(&fill_threshold) {
+        util::edit_reply(
+            ctx, Modal(modal), &format!(
+                "Error: fill threshold is not a number or doesn't fit in range {}-{}", MIN_FILL_THRESHOLD, MAX_FILL_THRESHOLD
+            )
+        ).await?;
+        
+        return Ok(());
+    }
+
+    if !(MIN_TIME_THRESHOLD..=MAX_TIME_THRESHOLD).contains(&time_threshold) {
+        util::edit_reply(
+            ctx, Modal(modal), &format!(
+                "Error: time threshold is not a number or doesn't fit in range {}-{}", MIN_TIME_THRESHOLD, MAX_TIME_THRESHOLD
+            )
+        ).await?;
+        
+        return Ok(());
+    }
+
+    let mut queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get_mut(&modal.channel_id) else {
+        util::edit_reply(
+            ctx, Modal(modal), "There is no queue set up in this channel!"
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let rule = match id {
+        Some(id) => {
+            let Some(rule) = queue.reminders.iter_mut().find(|rule| rule.id == id) else {
+                util::edit_reply(
+                    ctx, Modal(modal), "This reminder no longer exists!"
+                ).await?;
+                
+                return Ok(());
+            };
+
+            rule.fill_threshold = fill_threshold;
+            rule.time_threshold = time_threshold;
+            rule.insert(&data.inner.pool).await;
+
+            rule.clone()
+        },
+        None => {
+            if queue.reminders.len() >= MAX_REMINDER_RULES {
+                util::edit_reply(
+                    ctx, Modal(modal), &format!("Error: a queue can't have more than {} reminders", MAX_REMINDER_RULES)
+                ).await?;
+                
+                return Ok(());
+            }
+
+            let mut rule = ReminderRule::new(queue.channel, fill_threshold, time_threshold);
+            rule.insert(&data.inner.pool).await;
+
+            queue.reminders.push(rule.clone());
+
+            rule
+        }
+    };
+
+    let (embed, components) = create_edit_reminder_embed(queue, &rule);
+
+    drop(queues);
+
+    if let Err(err) = ctx.http().edit_original_interaction_response(
+        &token, 
+        &EditInteractionResponse::new().embed(embed).components(components), 
+        vec![]
+    ).await {
+        warn!("Error while editing interaction message: {err}");
+    }
+
+    modal.delete_response(ctx.http()).await?;
+
+    Ok(())
+}
+
+pub async fn spawn_reminder_message_form(
+    ctx: &Context, data: &Data, component: &ComponentInteraction, rule: &str
+) -> Result<(), Error> {
+    let existing = match rule.parse::<i64>() {
+        Ok(id) => data.inner.queues.lock().await.get(&component.channel_id).and_then(|queue| {
+            queue.reminders.iter().find(|rule| rule.id == id).cloned()
+        }),
+        Err(_) => None,
+    };
+
+    let Some(existing) = existing else {
+        util::direct_reply(ctx, Component(component), "This reminder no longer exists!", true).await?;
+
+        return Ok(());
+    };
+
+    let key = Uuid::new_v4().to_string();
+    data.inner.interaction_tokens.lock().await.insert(key.clone(), component.token.clone());
+
+    component.create_response(ctx.http(), CreateInteractionResponse::Modal(
+        CreateModal::new(format!("reminder-message-modal:{}:{}", key, existing.id), "Edit Reminder Message").components(
+            vec![CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Paragraph, "Message", "reminder-message"
+                ).placeholder(
+                    "Leave empty for the default, placeholders are listed on the edit page"
+                ).value(existing.message.unwrap_or_default()).max_length(MAX_REMINDER_MESSAGE_LENGTH as u16).required(false)
+            ), CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Send As", "reminder-format"
+                ).placeholder("'message', or 'embed' to add a link to the queue").value(
+                    if existing.embed { "embed" } else { "message" }
+                )
+            )]
+        )
+    )).await?;
+
+    Ok(())
+}
+
+pub async fn process_reminder_message_form(
+     ctx: &Context, data: &Data, modal: &ModalInteraction, key: &str
+) -> Result<(), Error> {
+    if let Some(message) = check_interaction_authorization(data, &modal.member).await {
+        util::direct_reply(ctx, Modal(modal), message, true).await?;
+        return Ok(());
+    }
+    
+    let components = &modal.data.components;
+    util::defer_ephemeral(ctx, Modal(modal)).await?;
+
+    let Some((key, id)) = key.split_once(':') else {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: invalid interaction"
+        ).await?;
+
+        return Ok(());
+    };
+
+    let Some(token) = data.inner.interaction_tokens.lock().await.remove(key) else {
+        util::edit_reply(
+            ctx, Modal(modal), "Error: invalid interaction"
+        ).await?;
+
+        return Ok(());
+    };
+
+    let mut message = None;
+    let mut format = None;
+
+    for row in components {
+        for component in &row.components {
+            if let ActionRowComponent::InputText(input) = component {
+                match input.custom_id.as_str() {
+                    "reminder-message" => message = input.value.clone(),
+                    "reminder-format" => format = input.value.clone(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let message = message.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    if message.as_ref().is_some_and(|v| v.chars().count() > MAX_REMINDER_MESSAGE_LENGTH) {
+        util::edit_reply(
+            ctx, Modal(modal), 
+            &format!("Error: reminder messages can be at most {} characters long", MAX_REMINDER_MESSAGE_LENGTH)
+        ).await?;
+
+        return Ok(());
+    }
+
+    let embed = match format.unwrap_or_default().trim().to_lowercase().as_str() {
+        "message" => false,
+        "embed" => true,
+        _ => {
+            util::edit_reply(
+                ctx, Modal(modal), "Error: reminders can only be sent as 'message' or 'embed'"
+            ).await?;
+            
+            return Ok(());
+        }
+    };
+
+    let mut queues = data.inner.queues.lock().await;
+
+    let Some(queue) = queues.get_mut(&modal.channel_id) else {
+        util::edit_reply(
+            ctx, Modal(modal), "There is no queue set up in this channel!"
+        ).await?;
+        
+        return Ok(());
+    };
+
+    let Some(rule) = queue.reminders.iter_mut().find(|rule| id.parse::<i64>() == Ok(rule.id)) else {
+        util::edit_reply(
+            ctx, Modal(modal), "This reminder no longer exists!"
+        ).await?;
+        
+        return Ok(());
+    };
+
+    rule.message = message;
+    rule.embed = embed;
+    rule.insert(&data.inner.pool).await;
+
+    let rule = rule.clone();
+    let (embed, components) = create_edit_reminder_embed(queue, &rule);
+
+    drop(queues);
+
+    if let Err(err) = ctx.http().edit_original_interaction_response(
+        &token, 
+        &EditInteractionResponse::new().embed(embed).components(components), 
+        vec![]
+    ).await {
+        warn!("Error while editing interaction message: {err}");
+    }
+
+    modal.delete_response(ctx.http()).await?;
+
+    Ok(())
+}
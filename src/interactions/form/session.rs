@@ -1,9 +1,11 @@
 use std::collections::hash_map::Entry;
 use serenity::all::{
     CacheHttp, ComponentInteraction, Context, CreateActionRow, CreateInputText, CreateInteractionResponse, 
-    CreateModal, InputTextStyle, ModalInteraction, ActionRowComponent, CreateMessage, Timestamp, UserId
+    CreateModal, InputTextStyle, ModalInteraction, ActionRowComponent, CreateMessage, Timestamp
 };
 
+use caramel::ns::format::prettify_name;
+
 use crate::api::calculate_telegram_delay;
 use crate::bot::{Data, Error, util::{self, Modal}};
 use crate::models::session::{RecruitDelay, Session, SESSION_TELEGRAM_BUFFER};
@@ -60,9 +62,29 @@ pub async fn process_session_form(
         }
     };
 
+    if user_data.suspended {
+        util::edit_reply(
+            ctx, util::Modal(modal), 
+            &format!("Error: {} is suspended because it left this queue's region or no longer exists! Please click 'Setup Templates' again.", prettify_name(&user_data.nation))
+        ).await?;
+
+        return Ok(());
+    }
+
+    let Some(batch_size) = data.inner.queues.lock().await.get(&modal.channel_id).map(
+        |v| v.batch_size_for(&user_data)
+    ) else {
+        util::edit_reply(
+            ctx, util::Modal(modal), 
+            "Invalid interaction: no queue linked to channel"
+        ).await?;
+
+        return Ok(());
+    };
+
     let delay = match delay.and_then(|v| v.parse::<u64>().ok()) {
         Some(delay) => {
-            let min_acceptable_delay = calculate_telegram_delay(user_data.founded) * 8 + SESSION_TELEGRAM_BUFFER;
+            let min_acceptable_delay = calculate_telegram_delay(user_data.founded) * batch_size as i64 + SESSION_TELEGRAM_BUFFER;
 
             if (delay as i64) < min_acceptable_delay {
                 util::edit_reply(
@@ -87,11 +109,11 @@ pub async fn process_session_form(
         None => RecruitDelay::Automatic
     };
 
-    match data.inner.sessions.lock().await.entry(modal.user.id) {
+    let session = match data.inner.sessions.lock().await.entry((modal.channel_id, modal.user.id)) {
         Entry::Occupied(_) => {
             util::edit_reply(
                 ctx, util::Modal(modal), 
-                "You already have a session in progress! Please stop the current session before starting a new one."
+                "You already have a session in progress for this queue! Please stop the current session before starting a new one."
             ).await?;
 
             return Ok(());
@@ -102,9 +124,11 @@ pub async fn process_session_form(
                 delay: delay.clone(),
                 last_activity_check: Timestamp::now(),
                 pause_time: None,
-            });
+            }).clone()
         },
-    }
+    };
+
+    session.insert(&data.inner.pool).await;
 
     // Add a 10-second cooldown before the first telegram, to avoid 
     // triggering "You are opening DMs too fast" when messaging new users for the first time
@@ -112,13 +136,14 @@ pub async fn process_session_form(
 
     let (embed, components) = create_session_start_embed(
         &user_data.nation,
-        &delay
+        &delay,
+        modal.channel_id,
     );
 
-    data.inner.cooldowns.lock().await.insert(
-        UserId::new(user_data.user_id), 
-        (cooldown, None)
-    );
+    // Don't cut short a cooldown this nation is already serving
+    data.inner.cooldowns.lock().await.entry(
+        user_data.nation.clone()
+    ).or_insert((cooldown, None));
 
     modal.user.direct_message(
         ctx.http(), CreateMessage::new().embed(embed).components(components)
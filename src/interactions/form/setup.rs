@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use serenity::all::{
     ActionRowComponent, CacheHttp, ComponentInteraction, Context, CreateActionRow, CreateInputText, CreateInteractionResponse, CreateModal, InputTextStyle, ModalInteraction
 };
@@ -6,7 +7,9 @@ use caramel::ns::format::{canonicalize_name, prettify_name};
 
 use crate::api::query_nation_data;
 use crate::bot::{Data, Error, util::{self, Modal}};
-use crate::models::user_data::UserData;
+use crate::models::{
+    queue::MAX_BATCH_SIZE, template::{Template, TemplateCategory, DEFAULT_TEMPLATE_WEIGHT, MAX_TEMPLATE_WEIGHT}, user_data::UserData
+};
 
 pub async fn spawn_setup_form(
     ctx: &Context, _: &Data, component: &ComponentInteraction
@@ -21,12 +24,22 @@ pub async fn spawn_setup_form(
             CreateActionRow::InputText(
                 CreateInputText::new(
                     InputTextStyle::Paragraph, "Newfound Templates", "session-newfound-input"
-                ).placeholder("Enter one template per line")
+                ).placeholder("One template per line, optionally followed by a weight. Manage existing ones with /template").required(false)
             ),
             CreateActionRow::InputText(
                 CreateInputText::new(
                     InputTextStyle::Paragraph, "Refound Templates", "session-refound-input"
-                ).placeholder("Enter one template per line")
+                ).placeholder("One template per line, optionally followed by a weight. Manage existing ones with /template").required(false)
+            ),
+            CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Batch Size", "session-batch-input"
+                ).placeholder(format!("Nations per telegram (1-{}), leave empty to use the queue's default", MAX_BATCH_SIZE)).required(false)
+            ),
+            CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short, "Template Selection", "session-selection-input"
+                ).placeholder("'weighted' (default) or 'adaptive' to favor templates with better move rates").required(false)
             )]
         )
     )).await?;
@@ -44,6 +57,8 @@ pub async fn process_setup_form(
     let mut nation = None;
     let mut newfound_templates = None;
     let mut refound_templates = None;
+    let mut batch_size = None;
+    let mut selection = None;
 
     for row in components {
         for component in &row.components {
@@ -52,6 +67,8 @@ pub async fn process_setup_form(
                     "session-nation-input" => nation = input.value.clone(),
                     "session-newfound-input" => newfound_templates = input.value.clone(),
                     "session-refound-input" => refound_templates = input.value.clone(),
+                    "session-batch-input" => batch_size = input.value.clone(),
+                    "session-selection-input" => selection = input.value.clone(),
                     _ => {}
                 }
             }
@@ -67,6 +84,33 @@ pub async fn process_setup_form(
         }
     };
 
+    let batch_size = match batch_size.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+        Some(v) => match v.parse::<i64>() {
+            Ok(size) if size >= 1 && size <= MAX_BATCH_SIZE as i64 => Some(size),
+            _ => {
+                util::edit_reply(
+                    ctx, Modal(modal), 
+                    &format!("Error: batch size is not a number or doesn't fit in range 1-{}", MAX_BATCH_SIZE)
+                ).await?;
+
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let adaptive = match selection.map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty()).as_deref() {
+        None | Some("weighted") => false,
+        Some("adaptive") => true,
+        Some(_) => {
+            util::edit_reply(
+                ctx, Modal(modal), "Error: template selection must be either 'weighted' or 'adaptive'"
+            ).await?;
+
+            return Ok(());
+        }
+    };
+
     let Some(region) = data.inner.queues.lock().await.get(&modal.channel_id).and_then(
         |v| Some(v.region.clone())
     ) else {
@@ -101,25 +145,41 @@ pub async fn process_setup_form(
         }
     };
 
-    let newfound_templates = newfound_templates.and_then(|s| Some(s.lines().map(
-        |s| s.trim().to_string()
-    ).filter(
-        |s| !s.is_empty()
-    ).collect::<Vec<_>>())).unwrap_or(vec![]);
+    let mut templates = data.inner.user_data.lock().await.get(&(modal.channel_id, modal.user.id)).map(
+        |v| v.templates.clone()
+    ).unwrap_or_default();
 
-    let refound_templates = refound_templates.and_then(|s| Some(s.lines().map(
-        |s| s.trim().to_string()
-    ).filter(
-        |s| !s.is_empty()
-    ).collect::<Vec<_>>())).unwrap_or(vec![]);
+    let mut updated = HashSet::new();
+
+    for (input, category) in [
+        (newfound_templates, TemplateCategory::Newfound), (refound_templates, TemplateCategory::Refound)
+    ] {
+        if let Err(message) = parse_template_lines(input, category, &mut templates, &mut updated) {
+            util::edit_reply(ctx, Modal(modal), &message).await?;
+
+            return Ok(());
+        }
+    }
+
+    // Templates are told apart by their id, so the setup can't go on with any that weren't saved
+    for template in templates.iter_mut().filter(|v| updated.contains(&v.code)) {
+        if template.insert(modal.channel_id, modal.user.id, &data.inner.pool).await.is_err() {
+            util::edit_reply(
+                ctx, Modal(modal), &format!("Error: failed to save template {}, please try again", template.code)
+            ).await?;
+
+            return Ok(());
+        }
+    }
 
     let user_data = UserData::new(
         modal.channel_id,
         modal.user.id,
         nation.clone(),
         founded,
-        newfound_templates,
-        refound_templates
+        batch_size,
+        adaptive,
+        templates,
     );
 
     user_data.insert(&data.inner.pool).await;
@@ -132,3 +192,59 @@ pub async fn process_setup_form(
 
     Ok(())
 }
+
+// Parses one template per line, each optionally followed by its weight (e.g. "%TEMPLATE-123456% 3"),
+// and adds or updates the matching templates. Templates listed under both categories are used for both.
+fn parse_template_lines(
+    input: Option<String>, category: TemplateCategory, templates: &mut Vec<Template>, updated: &mut HashSet<String>
+) -> Result<(), String> {
+    let mut invalid = Vec::new();
+
+    for line in input.as_deref().unwrap_or_default().lines() {
+        let mut parts = line.split_whitespace();
+
+        let Some(code) = parts.next() else {
+            continue;
+        };
+
+        if !Template::is_valid_code(code) {
+            invalid.push(format!("`{}`", line.trim().chars().take(50).collect::<String>()));
+            continue;
+        }
+
+        let weight = match parts.next() {
+            Some(weight) => match weight.parse::<u32>() {
+                Ok(weight) if (1..=MAX_TEMPLATE_WEIGHT).contains(&weight) => Some(weight),
+                _ => return Err(format!(
+                    "Error: invalid weight for {}, it must be a number between 1 and {}", code, MAX_TEMPLATE_WEIGHT
+                )),
+            },
+            None => None,
+        };
+
+        match templates.iter_mut().find(|v| v.code == code) {
+            Some(template) => {
+                template.category = if updated.contains(code) { template.category.union(category) } else { category };
+                template.enabled = true;
+
+                if let Some(weight) = weight {
+                    template.weight = weight;
+                }
+            },
+            None => templates.push(Template::new(
+                code.to_string(), code.to_string(), category, weight.unwrap_or(DEFAULT_TEMPLATE_WEIGHT)
+            )),
+        }
+
+        updated.insert(code.to_string());
+    }
+
+    if !invalid.is_empty() {
+        return Err(format!(
+            "Error: the following {} templates are not valid template codes (expected e.g. %TEMPLATE-123456%):\n{}",
+            category, invalid.join("\n")
+        ));
+    }
+
+    Ok(())
+}
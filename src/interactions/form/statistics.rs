@@ -4,6 +4,7 @@ use serenity::all::{
 };
 
 use crate::bot::{Data, Error, util::{self, Modal}};
+use crate::interactions::click::statistics_scope;
 use crate::models::report::ReportEntry;
 
 pub async fn spawn_stat_time_form(
@@ -29,14 +30,16 @@ pub async fn spawn_stat_time_form(
 pub async fn process_stat_leaders_custom_form(
     ctx: &Context, data: &Data, modal: &ModalInteraction
 ) -> Result<(), Error> {
-    if !data.inner.queues.lock().await.contains_key(&modal.channel_id) {
+    let scope = statistics_scope(ctx, data, modal.channel_id, &modal.member).await;
+
+    let Some((channels, _)) = scope else {
         util::direct_reply(
             ctx, Modal(modal),
             "Invalid interaction: no queue linked to channel", true
         ).await?;
 
         return Ok(());
-    }
+    };
 
     modal.defer_ephemeral(ctx.http()).await?;
 
@@ -45,7 +48,7 @@ pub async fn process_stat_leaders_custom_form(
     };
 
     let leaders = ReportEntry::count(
-        &data.inner.pool, modal.channel_id, Some(range)
+        &data.inner.pool, &channels, Some(range)
     ).await?;
 
     if leaders.is_empty() {
@@ -68,14 +71,16 @@ pub async fn process_stat_leaders_custom_form(
 pub async fn process_stat_csv_custom_form(
     ctx: &Context, data: &Data, modal: &ModalInteraction
 ) -> Result<(), Error> {
-    if !data.inner.queues.lock().await.contains_key(&modal.channel_id) {
+    let scope = statistics_scope(ctx, data, modal.channel_id, &modal.member).await;
+
+    let Some((channels, foreign)) = scope else {
         util::direct_reply(
             ctx, Modal(modal),
             "Invalid interaction: no queue linked to channel", true
         ).await?;
 
         return Ok(());
-    }
+    };
 
     modal.defer_ephemeral(ctx.http()).await?;
 
@@ -84,7 +89,7 @@ pub async fn process_stat_csv_custom_form(
     };
 
     let entries = ReportEntry::query(
-        &data.inner.pool, modal.channel_id, Some(range)
+        &data.inner.pool, &channels, Some(range)
     ).await?;
 
     if entries.is_empty() {
@@ -92,20 +97,7 @@ pub async fn process_stat_csv_custom_form(
             ctx, Modal(modal), "Error: no results recorded for this time period!"
         ).await?;
     } else {
-        let mut output: Vec<u8> = Vec::new();
-        let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(&mut output);
-
-        writer.write_record(&[
-            "Nation Name", "Event Type", "Event Source", "Queued at Time", 
-            "Recruiter Discord ID", "Sender Nation", "Telegram Template",
-            "Sent at Time", "Moved to Region?", "Moved at Time"
-        ])?;
-
-        for entry in entries {
-            writer.serialize(entry)?;
-        }
-
-        drop(writer);
+        let output = ReportEntry::write_csv(entries, &foreign)?;
 
         modal.edit_response(
 ctx.http(), EditInteractionResponse::new().content(format!(
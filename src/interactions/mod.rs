@@ -6,13 +6,13 @@ use serenity::all::{Context, ComponentInteraction, ModalInteraction, Member};
 
 use crate::bot::{Data, Error};
 
-pub fn check_interaction_authorization(member: &Option<Member>) -> Option<&'static str> {
+pub async fn check_interaction_authorization(data: &Data, member: &Option<Member>) -> Option<&'static str> {
     match member {
         None => {
             return Some("This interaction cannot be used in DMs!");
         },
         Some(member) => {
-            if !member.permissions.map_or(false, |p| p.manage_guild()) {
+            if !data.is_queue_admin(member).await {
                 return Some("You are not allowed to use this interaction!");
             }
 
@@ -24,6 +24,21 @@ pub fn check_interaction_authorization(member: &Option<Member>) -> Option<&'stat
 pub async fn handle_component_interaction(
     ctx: &Context, data: &Data, component: &ComponentInteraction
 ) -> Result<(), Error> {
+    if let Some((custom_id, key)) = component.data.custom_id.split_once(':') {
+        return match custom_id {
+            // Session DM buttons
+            "stream-resume" => click::handle_stream_resume(ctx, data, component, key).await,
+            "stream-end" => click::handle_stream_end(ctx, data, component, key).await,
+            // Reminder editing components
+            "edit-reminder" => form::spawn_reminder_form(ctx, data, component, Some(key)).await,
+            "edit-reminder-message" => form::spawn_reminder_message_form(ctx, data, component, key).await,
+            "delete-reminder" => click::handle_delete_reminder(ctx, data, component, key).await,
+            "edit-reminder-role" => dropdown::handle_edit_reminder_role(ctx, data, component, key).await,
+            "edit-reminder-channel" => dropdown::handle_edit_reminder_channel(ctx, data, component, key).await,
+            _ => Ok(()),
+        };
+    }
+
     match component.data.custom_id.as_str() {
         // Main embed buttons
         "recruit-oneshot" => click::handle_recruit_oneshot(ctx, data, component).await,
@@ -35,19 +50,19 @@ pub async fn handle_component_interaction(
         "stat-csv-all" => click::handle_stat_csv_all(ctx, data, component).await,
         "stat-leaders-custom" => form::spawn_stat_time_form(ctx, data, component, "stat-leaders-custom-report").await,
         "stat-csv-custom" => form::spawn_stat_time_form(ctx, data, component, "stat-csv-custom-report").await,
-        // Session DM buttons
-        "stream-resume" => click::handle_stream_resume(ctx, data, component).await,
-        "stream-end" => click::handle_stream_end(ctx, data, component).await,
         // Queue editing buttons
         "edit-queue-size" => form::spawn_queue_size_form(ctx, data, component).await,
         "edit-queue-regions" => form::spawn_queue_regions_form(ctx, data, component).await,
-        "edit-queue-threshold" => form::spawn_queue_threshold_form(ctx, data, component).await,
+        "edit-queue-reminder-schedule" => form::spawn_queue_reminder_schedule_form(ctx, data, component).await,
+        "add-queue-reminder" => form::spawn_reminder_form(ctx, data, component, None).await,
         "edit-queue-filter" => form::spawn_queue_filter_form(ctx, data, component).await,
-        "delete-queue-threshold" => click::handle_delete_queue_threshold(ctx, data, component).await,
-        "clear-queue-role-channel" => click::handle_clear_queue_role_and_channel(ctx, data, component).await,
+        "edit-queue-group" => form::spawn_queue_group_form(ctx, data, component).await,
+        "toggle-queue-drop-moved" => click::handle_toggle_queue_drop_moved(ctx, data, component).await,
+        "toggle-queue-share-statistics" => click::handle_toggle_queue_share_statistics(ctx, data, component).await,
+        "edit-queue-back" => click::handle_edit_queue_back(ctx, data, component).await,
         // Queue editing dropdowns
-        "edit-queue-role" => dropdown::handle_edit_queue_role(ctx, data, component).await,
-        "edit-queue-channel" => dropdown::handle_edit_queue_channel(ctx, data, component).await,
+        "edit-queue-reminder" => dropdown::handle_edit_queue_reminder(ctx, data, component).await,
+        "edit-queue-name-rules" => dropdown::handle_edit_queue_name_rules(ctx, data, component).await,
         _ => Ok(())
     }
 }
@@ -59,8 +74,11 @@ pub async fn handle_modal_interaction(
         match custom_id {
             "queue-size-modal" => form::process_queue_size_form(ctx, data, modal, key).await,
             "queue-regions-modal" => form::process_queue_regions_form(ctx, data, modal, key).await,
-            "queue-threshold-modal" => form::process_queue_threshold_form(ctx, data, modal, key).await,
+            "queue-reminder-schedule-modal" => form::process_queue_reminder_schedule_form(ctx, data, modal, key).await,
+            "reminder-modal" => form::process_reminder_form(ctx, data, modal, key).await,
+            "reminder-message-modal" => form::process_reminder_message_form(ctx, data, modal, key).await,
             "queue-filter-modal" => form::process_queue_filter_form(ctx, data, modal, key).await,
+            "queue-group-modal" => form::process_queue_group_form(ctx, data, modal, key).await,
             _ => Ok(()),
         }
     } else {
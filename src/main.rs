@@ -6,6 +6,7 @@ mod bot;
 mod config;
 mod embeds;
 mod api;
+mod feed;
 
 use caramel::ns::api::Client;
 use log::{warn, error};
@@ -45,13 +46,6 @@ async fn main() -> Result<(), Box<dyn Error>> {
         exit(1);
     });
 
-    let conn = lapin::Connection::connect(
-        &rabbitmq_url,
-        lapin::ConnectionProperties::default(),
-    ).await?;
-    
-    let channel = conn.create_channel().await?;
-
     let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|err| {
         error!("Missing DATABASE_URL environment variable: {err}");
         exit(1);
@@ -62,9 +56,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
         exit(1);
     });
 
+    sqlx::migrate!().run(&pool).await.unwrap_or_else(|err| {
+        error!("Error running database migrations: {}", err);
+        exit(1);
+    });
+
     let api_client = Client::new(user_agent.clone())?;
 
-    match start_client(token, pool, user_agent, channel, config, api_client).await {
+    match start_client(token, pool, user_agent, rabbitmq_url, config, api_client).await {
         Ok(_) => {},
         Err(err) => {
             error!("Error in discord client: {}", err);
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use log::warn;
+use serenity::all::{GuildId, RoleId};
+use sqlx::Row;
+
+// A role allowed to administer queues in a guild without the Manage Server permission.
+#[derive(Debug)]
+pub struct AdminRole {
+    pub guild: GuildId,
+    pub role: RoleId,
+}
+
+impl AdminRole {
+    pub fn new(guild: GuildId, role: RoleId) -> Self {
+        Self { guild, role }
+    }
+
+    pub async fn insert(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "INSERT INTO admin_roles (guild_id, role_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+            ).bind(self.guild.get() as i64)
+            .bind(self.role.get() as i64)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to save admin role '{:?}' to Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn remove(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "DELETE FROM admin_roles WHERE guild_id = $1 AND role_id = $2"
+            ).bind(self.guild.get() as i64)
+            .bind(self.role.get() as i64)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete admin role '{:?}' from Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<GuildId, Vec<RoleId>>, sqlx::Error> {
+        let vec = sqlx::query(
+            "SELECT guild_id, role_id FROM admin_roles"
+        ).fetch_all(pool).await?;
+
+        let mut map: HashMap<GuildId, Vec<RoleId>> = HashMap::new();
+        for value in vec {
+            map.entry(GuildId::new(value.get::<i64, &str>("guild_id") as u64)).or_default().push(
+                RoleId::new(value.get::<i64, &str>("role_id") as u64)
+            );
+        }
+
+        Ok(map)
+    }
+}
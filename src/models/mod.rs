@@ -1,4 +1,7 @@
+pub mod admin_role;
 pub mod queue;
+pub mod reminder;
 pub mod report;
 pub mod session;
+pub mod template;
 pub mod user_data;
\ No newline at end of file
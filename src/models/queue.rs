@@ -1,27 +1,128 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use caramel::ns::format::prettify_name;
+use chrono::Timelike;
+use chrono_tz::Tz;
 use itertools::Itertools;
 use log::warn;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use serenity::all::{
-    CacheHttp, ChannelId, Context, CreateActionRow, CreateEmbed, EditMessage, Mentionable, MessageId, RoleId, Timestamp, UserId
+    CacheHttp, ChannelId, Context, CreateActionRow, CreateEmbed, EditMessage, MessageId, Timestamp, UserId
 };
 
 use sqlx::{prelude::FromRow, Row};
 
-use crate::{embeds::create_queue_embed, models::user_data::UserData};
+use crate::{embeds::create_queue_embed, models::{reminder::{Reminder, ReminderRule, ReminderValues}, report::ReportEntry, template::Template, user_data::UserData}};
+
+// Named region groups that can be used in a queue's region filter, e.g. "@feeders".
+pub const REGION_GROUPS: &[(&str, &[&str])] = &[
+    ("feeders", &["the_north_pacific", "the_south_pacific", "the_east_pacific", "the_west_pacific", "the_pacific"]),
+    ("sinkers", &["the_rejected_realms", "lazarus", "osiris", "balder"]),
+];
+
+pub fn region_group(name: &str) -> Option<&'static [&'static str]> {
+    REGION_GROUPS.iter().find(|(group, _)| *group == name).map(|(_, regions)| *regions)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    // Nations from the listed regions are skipped
+    #[default]
+    Exclude,
+    // Only nations from the listed regions are queued
+    Include,
+}
+
+impl FilterMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "exclude" => Some(Self::Exclude),
+            "include" => Some(Self::Include),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exclude => "exclude",
+            Self::Include => "include",
+        }
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Filter {
+    pub mode: FilterMode,
+    // Region names, or region groups prefixed with '@'
     pub regions: Vec<String>,
+    // Raw patterns are kept so that one that fails to compile isn't lost when the queue is saved again
+    pub patterns: Vec<String>,
     pub regexes: Vec<Regex>,
 }
 
 impl Filter {
     pub fn matches(&self, region: &str) -> bool {
-        !self.regions.iter().any(|v| v == region)
+        let listed = self.regions.iter().any(|v| match v.strip_prefix('@') {
+            Some(group) => region_group(group).is_some_and(|regions| regions.contains(&region)),
+            None => v == region,
+        });
+
+        match self.mode {
+            FilterMode::Exclude => !listed,
+            FilterMode::Include => listed,
+        }
+    }
+}
+
+// Common prefixes of puppet nation names.
+const PUPPET_PREFIXES: &[&str] = &["puppet_", "pupp_", "alt_", "farm_", "storage_", "tag_"];
+const REPEATED_CHARACTERS: usize = 4;
+
+// Built-in heuristics for excluding nations by name, each of which can be turned on or off per queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameRule {
+    Numbered,
+    RomanNumeral,
+    RepeatedCharacters,
+    PuppetPrefix,
+}
+
+impl NameRule {
+    pub const ALL: [NameRule; 4] = [Self::Numbered, Self::RomanNumeral, Self::RepeatedCharacters, Self::PuppetPrefix];
+    pub const DEFAULT: [NameRule; 2] = [Self::Numbered, Self::RomanNumeral];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Numbered => "numbered",
+            Self::RomanNumeral => "roman_numeral",
+            Self::RepeatedCharacters => "repeated_characters",
+            Self::PuppetPrefix => "puppet_prefix",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Numbered => "Names ending in numbers",
+            Self::RomanNumeral => "Names ending in roman numerals",
+            Self::RepeatedCharacters => "Names with a character repeated 4 times in a row",
+            Self::PuppetPrefix => "Names starting with common puppet prefixes",
+        }
+    }
+
+    pub fn matches(&self, nation: &str) -> bool {
+        match self {
+            Self::Numbered => NUMBER_RE.is_match(nation),
+            Self::RomanNumeral => ROMAN_RE.is_match(nation),
+            Self::RepeatedCharacters => {
+                let chars = nation.chars().collect::<Vec<_>>();
+                chars.windows(REPEATED_CHARACTERS).any(|w| w.iter().all(|c| *c == w[0]))
+            },
+            Self::PuppetPrefix => PUPPET_PREFIXES.iter().any(|prefix| nation.starts_with(prefix)),
+        }
     }
 }
 
@@ -33,12 +134,77 @@ pub struct Nation {
     pub queue_time: Timestamp,
 }
 
+impl Nation {
+    async fn insert(&self, queue: ChannelId, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "INSERT INTO queued_nations (queue, name, region, event, queue_time)
+                VALUES ($1, $2, $3, $4, $5) ON CONFLICT (queue, name) DO NOTHING"
+            ).bind(queue.get() as i64)
+            .bind(&self.name)
+            .bind(&self.region)
+            .bind(&self.event)
+            .bind(self.queue_time.timestamp())
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to save queued nation '{:?}' to Postgres database - {:?}", self, result);
+        }
+    }
+
+    async fn remove_matching(queue: ChannelId, names: Vec<&str>, pool: &sqlx::PgPool) {
+        if names.is_empty() { return; }
+
+        let result = sqlx::query(
+           "DELETE FROM queued_nations WHERE queue = $1 AND name = ANY($2)"
+            ).bind(queue.get() as i64)
+            .bind(&names)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete queued nations {:?} from Postgres database - {:?}", names, result);
+        }
+    }
+
+    async fn query(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<ChannelId, Vec<Nation>>, sqlx::Error> {
+        let vec = sqlx::query(
+            "SELECT queue, name, region, event, queue_time FROM queued_nations ORDER BY id"
+        ).fetch_all(pool).await?;
+
+        let mut map: HashMap<ChannelId, Vec<Nation>> = HashMap::new();
+        for value in vec {
+            map.entry(ChannelId::new(value.get::<i64, &str>("queue") as u64)).or_default().push(Nation {
+                name: value.get::<String, &str>("name"),
+                region: value.get::<String, &str>("region"),
+                event: value.get::<String, &str>("event"),
+                queue_time: Timestamp::from_unix_timestamp(
+                    value.get::<i64, &str>("queue_time")
+                ).unwrap_or_else(|_| Timestamp::now()),
+            });
+        }
+
+        Ok(map)
+    }
+}
+
 #[derive(Debug, Default)]
 struct QueueImpl {
     nations: VecDeque<Nation>,
     dedup: HashSet<String>,
 }
 
+// A founded or refounded nation to be added to a queue.
+#[derive(Debug)]
+pub struct QueueEvent<'a> {
+    pub nation: &'a str,
+    pub event: &'a str,
+    pub region: &'a str,
+    pub queue_time: Timestamp,
+    // Latest telegram sent to the nation from any queue in the queue's group
+    pub last_sent: Option<i64>,
+}
+
 #[derive(Debug)]
 pub struct QueueMessageUpdate {
     embed: CreateEmbed,
@@ -65,7 +231,35 @@ impl QueueMessageUpdate {
 }
 
 pub const QUEUE_TELEGRAM_BUFFER: i64 = 5; // 5 seconds past normal telegram cooldown
-const REMINDER_COOLDOWN: i64 = 6 * 3600; // Four hours at least between each reminder ping.
+pub const DEFAULT_BATCH_SIZE: usize = 8;
+pub const MAX_BATCH_SIZE: usize = 8; // NationStates caps manual recruitment telegrams at 8 recipients
+pub const DEFAULT_RESEND_WINDOW: u64 = 30; // Nations telegrammed in the last 30 days aren't queued again
+pub const MAX_RESEND_WINDOW: u64 = 365;
+pub const DEFAULT_REMINDER_COOLDOWN: u64 = 6 * 60; // Six hours at least between each reminder ping, in minutes
+pub const MIN_REMINDER_COOLDOWN: u64 = 30;
+pub const MAX_REMINDER_COOLDOWN: u64 = 24 * 60;
+
+// Parses quiet hours written as "22-8", i.e. the starting and ending hour in the queue's time zone.
+pub fn parse_quiet_hours(value: &str) -> Option<(u32, u32)> {
+    let (start, end) = value.split_once('-')?;
+    let start = start.trim().parse::<u32>().ok()?;
+    let end = end.trim().parse::<u32>().ok()?;
+
+    if start > 23 || end > 23 || start == end {
+        return None;
+    }
+
+    Some((start, end))
+}
+
+// Whether the hour falls within the quiet hours, which wrap around midnight when they end before they start.
+fn is_quiet_hour((start, end): (u32, u32), hour: u32) -> bool {
+    if start < end {
+        hour >= start && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
 
 #[derive(Debug, FromRow)]
 pub struct Queue {
@@ -74,17 +268,24 @@ pub struct Queue {
     pub region: String,
     pub filter: Filter,
     pub size: usize,
-    pub thresholds: Option<(u64, u64)>,
-    pub ping_channel: Option<ChannelId>,
-    pub ping_role: Option<RoleId>,
+    pub batch_size: usize,
+    pub drop_moved: bool,
+    pub group: Option<String>,
+    pub share_statistics: bool,
+    pub resend_window: u64,
+    pub skipped_resends: u64,
+    pub name_rules: Vec<NameRule>,
+    pub reminder_cooldown: u64,
+    pub quiet_hours: Option<(u32, u32)>,
+    pub timezone: Tz,
+    #[sqlx(skip)]
+    pub reminders: Vec<ReminderRule>,
     #[sqlx(skip)]
     queue: QueueImpl,
     #[sqlx(skip)]
     last_update: Timestamp,
     #[sqlx(skip)]
     last_telegram: Option<(Timestamp, UserId)>,
-    #[sqlx(skip)]
-    last_reminder: Timestamp,
 }
 
 lazy_static! {
@@ -106,13 +307,20 @@ impl Queue {
             region,
             filter,
             size,
-            thresholds: None,
-            ping_channel: None,
-            ping_role: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            drop_moved: false,
+            group: None,
+            share_statistics: false,
+            resend_window: DEFAULT_RESEND_WINDOW,
+            skipped_resends: 0,
+            name_rules: NameRule::DEFAULT.to_vec(),
+            reminder_cooldown: DEFAULT_REMINDER_COOLDOWN,
+            quiet_hours: None,
+            timezone: Tz::UTC,
+            reminders: Vec::new(),
             queue: QueueImpl::default(),
             last_update: Timestamp::now(),
             last_telegram: None,
-            last_reminder: Timestamp::now(),
         }
     }
 
@@ -120,6 +328,10 @@ impl Queue {
         self.queue.nations.len()
     }
 
+    pub fn nation_names(&self) -> impl Iterator<Item = &str> {
+        self.queue.nations.iter().map(|v| v.name.as_str())
+    }
+
     pub fn last_updated(&self) -> Timestamp {
         self.last_update
     }
@@ -128,13 +340,20 @@ impl Queue {
         self.last_telegram
     }
 
-    pub fn add(&mut self, nation: Nation) -> bool {
+    // Amount of nations to hand out per telegram, honoring the recruiter's override if they set one.
+    pub fn batch_size_for(&self, data: &UserData) -> usize {
+        data.batch_size.map_or(self.batch_size, |v| v as usize)
+    }
+
+    pub async fn add(&mut self, nation: Nation, pool: &sqlx::PgPool) -> bool {
         if self.queue.dedup.insert(nation.name.clone()) {
+            nation.insert(self.channel, pool).await;
             self.queue.nations.push_back(nation);
 
             if self.queue.nations.len() > self.size {
                 if let Some(old_nation) = self.queue.nations.pop_front() {
                     self.queue.dedup.remove(&old_nation.name);
+                    Nation::remove_matching(self.channel, vec![&old_nation.name], pool).await;
                 }
             }
 
@@ -146,45 +365,47 @@ impl Queue {
         }
     }
 
-    pub fn pull(
-        &mut self, data: &UserData, mut limit: usize, sessions: Vec<UserId>,
-    ) -> (Vec<Nation>, Vec<String>, Option<QueueMessageUpdate>) {
-        if self.queue.nations.is_empty() { return (vec![], vec![], None); }
+    pub async fn remove_nation(
+        &mut self, nation: &str, sessions: Vec<UserId>, pool: &sqlx::PgPool,
+    ) -> Option<QueueMessageUpdate> {
+        if !self.queue.dedup.remove(nation) {
+            return None;
+        }
+
+        self.queue.nations.retain(|v| v.name != nation);
+        Nation::remove_matching(self.channel, vec![nation], pool).await;
+
+        Some(self.generate_queue_update(sessions))
+    }
+
+    pub async fn pull(
+        &mut self, data: &UserData, mut limit: usize, sessions: Vec<UserId>, pool: &sqlx::PgPool,
+    ) -> (Vec<Nation>, Vec<Template>, Option<QueueMessageUpdate>) {
+        if self.queue.nations.is_empty() || data.suspended { return (vec![], vec![], None); }
 
         let mut indexes: Vec<usize> = Vec::new();
         let mut pos: usize = self.queue.nations.len() - 1;
 
-        let mut eligible_templates: Option<Vec<String>> = None;
+        let mut eligible_templates: Option<Vec<Template>> = None;
 
         while limit > 0 {
             if let Some(nation) = self.queue.nations.get(pos) {
+                let templates = data.templates_for(&nation.event);
+
                 if let Some(el_templates) = &mut eligible_templates {
-                    let intersection: Vec<String> = match nation.event.as_str() {
-                        "newfound" => Some(&data.newfounds),
-                        "refound" => Some(&data.refounds),
-                        _ => None,
-                    }.and_then(
-                        |v| Some(v.iter().filter(
-                            |v| el_templates.contains(v)
-                        ).cloned().collect())
-                    ).unwrap_or(vec![]);
+                    let intersection: Vec<Template> = el_templates.iter().filter(
+                        |v| templates.iter().any(|t| t.id == v.id)
+                    ).cloned().collect();
 
                     if !intersection.is_empty() {
                         eligible_templates = Some(intersection);
                         limit -= 1;
                         indexes.push(pos);
                     }
-                } else {
-                    eligible_templates = match nation.event.as_str() {
-                        "newfound" => Some(&data.newfounds),
-                        "refound" => Some(&data.refounds),
-                        _ => None,
-                    }.cloned();
-
-                    if !eligible_templates.as_ref().and_then(|v| Some(v.is_empty())).unwrap_or(true) {
-                        limit -= 1;
-                        indexes.push(pos);
-                    }
+                } else if !templates.is_empty() {
+                    eligible_templates = Some(templates.into_iter().cloned().collect());
+                    limit -= 1;
+                    indexes.push(pos);
                 }
             }
 
@@ -201,6 +422,8 @@ impl Queue {
             }
         }).collect::<Vec<_>>(), eligible_templates.unwrap_or(vec![]));
 
+        Nation::remove_matching(self.channel, nations.iter().map(|v| v.name.as_str()).collect(), pool).await;
+
         let update = if !nations.is_empty() {
             self.last_telegram = Some((Timestamp::now(), UserId::new(data.user_id)));
 
@@ -212,20 +435,115 @@ impl Queue {
         (nations, templates, update)
     }
 
+    // Removes nations pulled by a queue from the other queues in its group, so they aren't telegrammed twice.
+    pub async fn remove_from_group(
+        queues: &mut HashMap<ChannelId, Queue>, channel: ChannelId, nations: &[Nation],
+        sessions: &[(ChannelId, UserId)], pool: &sqlx::PgPool,
+    ) -> Vec<QueueMessageUpdate> {
+        let Some(group) = queues.get(&channel).and_then(|v| v.group.clone()) else {
+            return vec![];
+        };
+
+        let mut updates = Vec::new();
+
+        for queue in queues.values_mut() {
+            if queue.channel == channel || queue.group.as_ref() != Some(&group) {
+                continue;
+            }
+
+            let names = nations.iter().filter(
+                |v| queue.queue.dedup.remove(&v.name)
+            ).map(|v| v.name.as_str()).collect::<Vec<_>>();
+
+            if names.is_empty() {
+                continue;
+            }
+
+            queue.queue.nations.retain(|v| !names.contains(&v.name.as_str()));
+            Nation::remove_matching(queue.channel, names, pool).await;
+
+            updates.push(queue.generate_queue_update(
+                sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1) } else { None }).collect()
+            ));
+        }
+
+        updates
+    }
+
+    // The queue's channel along with the channels of every other queue in its group.
+    pub fn group_channels(queues: &HashMap<ChannelId, Queue>, channel: ChannelId) -> Option<Vec<ChannelId>> {
+        let queue = queues.get(&channel)?;
+
+        let Some(group) = &queue.group else {
+            return Some(vec![channel]);
+        };
+
+        Some(queues.values().filter(|v| v.group.as_ref() == Some(group)).map(|v| v.channel).collect())
+    }
+
+    // Latest telegram sent to a nation from any queue in each queue's group, given the latest
+    // telegram sent to it from each queue.
+    pub fn group_last_sent(queues: &HashMap<ChannelId, Queue>, sent: &HashMap<ChannelId, i64>) -> HashMap<ChannelId, i64> {
+        if sent.is_empty() {
+            return HashMap::new();
+        }
+
+        queues.keys().filter_map(|channel| {
+            let channels = Queue::group_channels(queues, *channel)?;
+            let time = channels.iter().filter_map(|v| sent.get(v)).max()?;
+
+            Some((*channel, *time))
+        }).collect()
+    }
+
+    // The queue's channel along with the channels of every other queue in its group that shares its
+    // statistics. Queues that don't share their statistics only see their own.
+    pub fn statistics_channels(queues: &HashMap<ChannelId, Queue>, channel: ChannelId) -> Option<Vec<ChannelId>> {
+        let queue = queues.get(&channel)?;
+
+        let (Some(group), true) = (&queue.group, queue.share_statistics) else {
+            return Some(vec![channel]);
+        };
+
+        Some(queues.values().filter(|v| v.group.as_ref() == Some(group) && v.share_statistics).map(|v| v.channel).collect())
+    }
+
     pub async fn query(
         pool: &sqlx::PgPool,
     ) -> Result<HashMap<ChannelId, Queue>, sqlx::Error> {
         let vec = sqlx::query(
-       "SELECT channel_id, message_id, region, size, excluded_regions, 
-            fill_threshold, time_threshold, ping_channel, ping_role, regex_filters FROM queues"
+       "SELECT channel_id, message_id, region, size, batch_size, region_filter_mode, filter_regions, 
+            regex_filters, drop_moved, group_code, share_statistics, resend_window, skipped_resends, name_rules, 
+            reminder_cooldown, quiet_start, quiet_end, timezone FROM queues"
         ).fetch_all(pool).await?;
 
+        let mut nations = Nation::query(pool).await?;
+        let mut reminders = ReminderRule::query(pool).await?;
+        let mut last_telegrams = ReportEntry::query_last_telegrams(pool).await?;
+
         let mut map = HashMap::new();
         for value in vec {
             let channel = ChannelId::new(value.get::<i64, &str>("channel_id") as u64);
-            let fill_threshold = value.get::<Option<i64>, &str>("fill_threshold").and_then(|v| Some(v as u64));
-            let time_threshold = value.get::<Option<i64>, &str>("time_threshold").and_then(|v| Some(v as u64));
-            let regexes = value.get::<Vec<String>, &str>("regex_filters").into_iter().filter_map(|v| Regex::new(&v).ok()).collect();
+            let quiet_start = value.get::<Option<i32>, &str>("quiet_start").map(|v| v as u32);
+            let quiet_end = value.get::<Option<i32>, &str>("quiet_end").map(|v| v as u32);
+            let patterns = value.get::<Vec<String>, &str>("regex_filters");
+            let regexes = patterns.iter().filter_map(|v| match Regex::new(v) {
+                Ok(regex) => Some(regex),
+                Err(err) => {
+                    warn!("Ignoring invalid regex filter '{}' for queue {}: {}", v, channel, err);
+                    None
+                }
+            }).collect();
+
+            let mut queue = QueueImpl::default();
+            for nation in nations.remove(&channel).unwrap_or_default() {
+                if queue.dedup.insert(nation.name.clone()) {
+                    queue.nations.push_back(nation);
+                }
+            }
+
+            let last_update = queue.nations.back().map_or(Timestamp::now(), |v| v.queue_time);
+
             map.insert(
                 channel,
                 Queue {
@@ -233,17 +551,26 @@ impl Queue {
                     message: MessageId::new(value.get::<i64, &str>("message_id") as u64),
                     region: value.get::<String, &str>("region"),
                     filter: Filter { 
-                        regions: value.get::<Vec<String>, &str>("excluded_regions"),
+                        mode: FilterMode::parse(&value.get::<String, &str>("region_filter_mode")).unwrap_or_default(),
+                        regions: value.get::<Vec<String>, &str>("filter_regions"),
+                        patterns,
                         regexes
                     },
                     size: value.get::<i64, &str>("size") as usize,
-                    thresholds: fill_threshold.zip(time_threshold),
-                    ping_channel: value.get::<Option<i64>, &str>("ping_channel").and_then(|v| Some(ChannelId::new(v as u64))),
-                    ping_role: value.get::<Option<i64>, &str>("ping_role").and_then(|v| Some(RoleId::new(v as u64))),
-                    queue: QueueImpl::default(),
-                    last_update: Timestamp::now(),
-                    last_telegram: None,
-                    last_reminder: Timestamp::now(),
+                    batch_size: value.get::<i64, &str>("batch_size") as usize,
+                    drop_moved: value.get::<bool, &str>("drop_moved"),
+                    group: value.get::<Option<String>, &str>("group_code"),
+                    share_statistics: value.get::<bool, &str>("share_statistics"),
+                    resend_window: value.get::<i64, &str>("resend_window") as u64,
+                    skipped_resends: value.get::<i64, &str>("skipped_resends") as u64,
+                    name_rules: value.get::<Vec<String>, &str>("name_rules").iter().filter_map(|v| NameRule::parse(v)).collect(),
+                    reminder_cooldown: value.get::<i64, &str>("reminder_cooldown") as u64,
+                    quiet_hours: quiet_start.zip(quiet_end),
+                    timezone: value.get::<String, &str>("timezone").parse::<Tz>().unwrap_or(Tz::UTC),
+                    reminders: reminders.remove(&channel).unwrap_or_default(),
+                    queue,
+                    last_update,
+                    last_telegram: last_telegrams.remove(&channel),
                 }
             );
         }
@@ -251,33 +578,63 @@ impl Queue {
         Ok(map)
     }
 
+    // Latest time a nation was queued here, either still in the queue or already telegrammed.
+    pub async fn query_last_queue_time(
+        &self,
+        pool: &sqlx::PgPool
+    ) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar(
+       "SELECT MAX(queue_time) FROM (
+                SELECT queue_time FROM queued_nations WHERE queue = $1
+                UNION ALL
+                SELECT queue_time FROM delivery_reports WHERE queue = $1
+            ) AS times"
+        ).bind(self.channel.get() as i64)
+        .fetch_one(pool).await
+    }
+
     pub async fn insert(
         &self,
         pool: &sqlx::PgPool
     ) {
         let result = sqlx::query(
-           "INSERT INTO queues (channel_id, message_id, region, size, excluded_regions, 
-                fill_threshold, time_threshold, ping_channel, ping_role, regex_filters)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT (channel_id) DO UPDATE
+           "INSERT INTO queues (channel_id, message_id, region, size, filter_regions, 
+                regex_filters, drop_moved, batch_size, group_code, resend_window, region_filter_mode, name_rules,
+                reminder_cooldown, quiet_start, quiet_end, timezone, share_statistics)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) ON CONFLICT (channel_id) DO UPDATE
                 SET message_id = EXCLUDED.message_id,
                 region = EXCLUDED.region,
                 size = EXCLUDED.size,
-                excluded_regions = EXCLUDED.excluded_regions,
-                fill_threshold = EXCLUDED.fill_threshold,
-                time_threshold = EXCLUDED.time_threshold,
-                ping_channel = EXCLUDED.ping_channel,
-                ping_role = EXCLUDED.ping_role,
-                regex_filters = EXCLUDED.regex_filters"
+                batch_size = EXCLUDED.batch_size,
+                filter_regions = EXCLUDED.filter_regions,
+                region_filter_mode = EXCLUDED.region_filter_mode,
+                regex_filters = EXCLUDED.regex_filters,
+                drop_moved = EXCLUDED.drop_moved,
+                group_code = EXCLUDED.group_code,
+                resend_window = EXCLUDED.resend_window,
+                name_rules = EXCLUDED.name_rules,
+                reminder_cooldown = EXCLUDED.reminder_cooldown,
+                quiet_start = EXCLUDED.quiet_start,
+                quiet_end = EXCLUDED.quiet_end,
+                timezone = EXCLUDED.timezone,
+                share_statistics = EXCLUDED.share_statistics"
             ).bind(self.channel.get() as i64)
             .bind(self.message.get() as i64)
             .bind(&self.region)
             .bind(self.size as i64)
             .bind(&self.filter.regions)
-            .bind(self.thresholds.and_then(|v| Some(v.0 as i64)))
-            .bind(self.thresholds.and_then(|v| Some(v.1 as i64)))
-            .bind(self.ping_channel.and_then(|v| Some(v.get() as i64)))
-            .bind(self.ping_role.and_then(|v| Some(v.get() as i64)))
-            .bind(self.filter.regexes.iter().map(|v| v.as_str()).collect::<Vec<_>>())
+            .bind(&self.filter.patterns)
+            .bind(self.drop_moved)
+            .bind(self.batch_size as i64)
+            .bind(&self.group)
+            .bind(self.resend_window as i64)
+            .bind(self.filter.mode.as_str())
+            .bind(self.name_rules.iter().map(|v| v.as_str()).collect::<Vec<_>>())
+            .bind(self.reminder_cooldown as i64)
+            .bind(self.quiet_hours.map(|v| v.0 as i32))
+            .bind(self.quiet_hours.map(|v| v.1 as i32))
+            .bind(self.timezone.name())
+            .bind(self.share_statistics)
             .execute(pool).await;
 
         if result.is_err() {
@@ -297,6 +654,15 @@ impl Queue {
         if result.is_err() {
             warn!("Failed to delete queue '{:?}' from Postgres database - {:?}", self, result);
         }
+
+        let result = sqlx::query(
+           "DELETE FROM queued_nations WHERE queue = $1"
+            ).bind(self.channel.get() as i64)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete queued nations for queue '{:?}' from Postgres database - {:?}", self.channel, result);
+        }
     }
 
     pub fn generate_queue_update(&self, sessions: Vec<UserId>) -> QueueMessageUpdate {
@@ -304,77 +670,221 @@ impl Queue {
         QueueMessageUpdate { embed, components, channel: self.channel, message: self.message }
     }
 
-    pub fn add_to_queue(
+    pub async fn add_to_queue(
         &mut self,
-        nation: &str,
-        event: &str,
-        region: &str,
+        event: QueueEvent<'_>,
         sessions: Vec<UserId>,
+        pool: &sqlx::PgPool,
     ) -> Option<QueueMessageUpdate> {
-        if region == self.region 
-        || !self.filter.matches(region) {
+        if event.region == self.region 
+        || !self.filter.matches(event.region) {
             return None;
         }
 
-        if NUMBER_RE.is_match(nation)
-        || ROMAN_RE.is_match(nation) {
+        if self.name_rules.iter().any(|rule| rule.matches(event.nation)) {
             return None;
         }
 
         for filter in &self.filter.regexes {
-            if filter.is_match(nation) {
+            if filter.is_match(event.nation) {
+                return None;
+            }
+        }
+
+        if self.resend_window > 0 && !self.queue.dedup.contains(event.nation) {
+            let since = Timestamp::now().timestamp() - (self.resend_window * 24 * 3600) as i64;
+
+            if event.last_sent.is_some_and(|v| v >= since) {
+                self.record_skipped_resend(pool).await;
                 return None;
             }
         }
 
         if self.add(
             Nation { 
-                name: nation.to_owned(), region: region.to_owned(), 
-                event: event.to_owned(), queue_time: Timestamp::now() 
-            }
-        ) {
+                name: event.nation.to_owned(), region: event.region.to_owned(), 
+                event: event.event.to_owned(), queue_time: event.queue_time 
+            }, pool
+        ).await {
             Some(self.generate_queue_update(sessions))
         } else {
             None
         }
     }
 
-    pub fn make_reminder_if_needed(&mut self) -> Option<(ChannelId, String)> {
-        let Some((fill_threshold, time_threshold)) = self.thresholds else {
-            return None;
-        };
+    async fn record_skipped_resend(&mut self, pool: &sqlx::PgPool) {
+        self.skipped_resends += 1;
 
-        let Some(channel) = self.ping_channel else {
-            return None;
-        };
+        let result = sqlx::query(
+           "UPDATE queues SET skipped_resends = skipped_resends + 1 WHERE channel_id = $1"
+            ).bind(self.channel.get() as i64)
+            .execute(pool).await;
 
-        let Some(role) = self.ping_role else {
-            return None;
+        if result.is_err() {
+            warn!("Failed to record skipped nation for queue '{:?}' in Postgres database - {:?}", self.channel, result);
+        }
+    }
+
+    // Whether the current time falls within the queue's quiet hours, in its time zone.
+    pub fn in_quiet_hours(&self) -> bool {
+        let Some(quiet_hours) = self.quiet_hours else {
+            return false;
         };
 
+        is_quiet_hour(quiet_hours, chrono::Utc::now().with_timezone(&self.timezone).hour())
+    }
+
+    pub fn reminder_values(&self) -> ReminderValues {
         let now = Timestamp::now();
-        let time_since_last_reminder = now.timestamp() - self.last_reminder.timestamp();
         let time_since_last_telegram = now.timestamp() - self.last_telegram.unwrap_or(
             (Timestamp::now(), UserId::default())
         ).0.timestamp();
 
-        if time_since_last_reminder < REMINDER_COOLDOWN
-        || time_since_last_telegram < (time_threshold * 60) as i64
-        || self.queue.nations.len() < fill_threshold as usize {
-            return None;
+        ReminderValues {
+            region: self.region.clone(),
+            count: self.queue.nations.len(),
+            minutes: time_since_last_telegram / 60,
+            last_recruiter: self.last_telegram.map(|(_, user)| user),
+            oldest_queued: self.queue.nations.front().map(|nation| nation.queue_time),
         }
+    }
 
-        self.last_reminder = now;
+    pub fn make_reminder_if_needed(&mut self) -> Vec<Reminder> {
+        // Pings are held back until the quiet hours end, when they go out if the criteria are still met
+        if self.in_quiet_hours() {
+            return Vec::new();
+        }
 
-        Some((
-            channel,
-            format!(
-                "{} - it's been over {} minutes since someone sent a telegram and the {} queue is over {} nations, time to recruit!",
-                role.mention(),
-                time_threshold,
-                prettify_name(&self.region),
-                fill_threshold
-            )
-        ))
-    }
-}
\ No newline at end of file
+        let now = Timestamp::now();
+        let values = self.reminder_values();
+
+        // Each rule has its own cooldown, so a higher tier can still ping after a lower one did
+        self.reminders.iter_mut().filter_map(|rule| {
+            let channel = rule.channel?;
+            let on_cooldown = rule.last_reminder.is_some_and(|time| {
+                now.timestamp() - time.timestamp() < (self.reminder_cooldown * 60) as i64
+            });
+
+            if on_cooldown
+            || values.minutes < rule.time_threshold as i64
+            || values.count < rule.fill_threshold as usize {
+                return None;
+            }
+
+            rule.last_reminder = Some(now);
+
+            Some(Reminder {
+                rule: rule.id,
+                channel,
+                role: rule.role,
+                text: rule.format_message(&values),
+                embed: rule.embed,
+                region: self.region.clone(),
+                queue: self.channel,
+                message: self.message,
+            })
+        }).collect()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(mode: FilterMode, regions: &[&str]) -> Filter {
+        Filter { mode, regions: regions.iter().map(|v| v.to_string()).collect(), ..Default::default() }
+    }
+
+    #[test]
+    fn exclude_filter_skips_listed_regions() {
+        let filter = filter(FilterMode::Exclude, &["lazarus"]);
+
+        assert!(!filter.matches("lazarus"));
+        assert!(filter.matches("osiris"));
+    }
+
+    #[test]
+    fn include_filter_only_accepts_listed_regions() {
+        let filter = filter(FilterMode::Include, &["lazarus"]);
+
+        assert!(filter.matches("lazarus"));
+        assert!(!filter.matches("osiris"));
+    }
+
+    #[test]
+    fn filters_expand_region_groups() {
+        let exclude = filter(FilterMode::Exclude, &["@feeders"]);
+        assert!(!exclude.matches("the_north_pacific"));
+        assert!(exclude.matches("lazarus"));
+
+        let include = filter(FilterMode::Include, &["@sinkers", "the_pacific"]);
+        assert!(include.matches("lazarus"));
+        assert!(include.matches("the_pacific"));
+        assert!(!include.matches("the_north_pacific"));
+    }
+
+    #[test]
+    fn filters_ignore_unknown_region_groups() {
+        assert!(filter(FilterMode::Exclude, &["@unknown"]).matches("lazarus"));
+        assert!(!filter(FilterMode::Include, &["@unknown"]).matches("lazarus"));
+    }
+
+    #[test]
+    fn repeated_characters_needs_four_in_a_row() {
+        assert!(NameRule::RepeatedCharacters.matches("aaaa"));
+        assert!(NameRule::RepeatedCharacters.matches("the_zzzz_empire"));
+        assert!(NameRule::RepeatedCharacters.matches("nation____"));
+        assert!(!NameRule::RepeatedCharacters.matches("aaa_aaa"));
+        assert!(!NameRule::RepeatedCharacters.matches("aaa"));
+        assert!(!NameRule::RepeatedCharacters.matches(""));
+    }
+
+    #[test]
+    fn puppet_prefix_matches_each_prefix() {
+        for prefix in PUPPET_PREFIXES {
+            assert!(NameRule::PuppetPrefix.matches(&format!("{}nation", prefix)), "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn puppet_prefix_needs_the_separator() {
+        assert!(!NameRule::PuppetPrefix.matches("puppeteer_republic"));
+        assert!(!NameRule::PuppetPrefix.matches("altitude"));
+        assert!(!NameRule::PuppetPrefix.matches("farmland"));
+        assert!(!NameRule::PuppetPrefix.matches("my_puppet_nation"));
+    }
+
+    #[test]
+    fn parses_quiet_hours() {
+        assert_eq!(parse_quiet_hours("22-8"), Some((22, 8)));
+        assert_eq!(parse_quiet_hours(" 0 - 6 "), Some((0, 6)));
+        assert_eq!(parse_quiet_hours("8-8"), None);
+        assert_eq!(parse_quiet_hours("22-24"), None);
+        assert_eq!(parse_quiet_hours("22"), None);
+        assert_eq!(parse_quiet_hours("night-8"), None);
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        assert!(!is_quiet_hour((1, 6), 0));
+        assert!(is_quiet_hour((1, 6), 1));
+        assert!(is_quiet_hour((1, 6), 5));
+        assert!(!is_quiet_hour((1, 6), 6));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_around_midnight() {
+        assert!(!is_quiet_hour((22, 8), 21));
+        assert!(is_quiet_hour((22, 8), 22));
+        assert!(is_quiet_hour((22, 8), 23));
+        assert!(is_quiet_hour((22, 8), 0));
+        assert!(is_quiet_hour((22, 8), 7));
+        assert!(!is_quiet_hour((22, 8), 8));
+    }
+
+    #[test]
+    fn queues_without_quiet_hours_are_never_quiet() {
+        let queue = Queue::new(ChannelId::new(1), MessageId::new(1), "lazarus".into(), Filter::default(), 10);
+
+        assert!(!queue.in_quiet_hours());
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use log::warn;
+use serenity::all::{ChannelId, FormattedTimestamp, FormattedTimestampStyle, Mentionable, MessageId, RoleId, Timestamp, UserId};
+use sqlx::Row;
+
+use caramel::ns::format::prettify_name;
+
+pub const MIN_FILL_THRESHOLD: u64 = 30;
+pub const MAX_FILL_THRESHOLD: u64 = 500;
+pub const MIN_TIME_THRESHOLD: u64 = 30;
+pub const MAX_TIME_THRESHOLD: u64 = 360;
+pub const MAX_REMINDER_RULES: usize = 25; // Discord select menus hold at most 25 options
+pub const MAX_REMINDER_MESSAGE_LENGTH: usize = 1000;
+const MAX_REMINDER_TEXT_LENGTH: usize = 2000; // Discord's limit on the length of a message
+pub const DEFAULT_REMINDER_MESSAGE: &str = 
+    "{role} It's been {minutes} minutes since someone sent a telegram and the {region} queue has {count} nations, time to recruit!";
+pub const REMINDER_PLACEHOLDERS: &str = "{role}, {region}, {count}, {minutes}, {last_recruiter}, {oldest_queued}";
+
+// Current state of a queue, filled into the placeholders of a reminder message.
+pub struct ReminderValues {
+    pub region: String,
+    pub count: usize,
+    pub minutes: i64,
+    pub last_recruiter: Option<UserId>,
+    pub oldest_queued: Option<Timestamp>,
+}
+
+// A reminder ready to be sent, along with the queue message it links to when sent as an embed.
+pub struct Reminder {
+    pub rule: i64,
+    pub channel: ChannelId,
+    pub role: Option<RoleId>,
+    pub text: String,
+    pub embed: bool,
+    pub region: String,
+    pub queue: ChannelId,
+    pub message: MessageId,
+}
+
+// A reminder sent when the queue is over a fill threshold and nobody has telegrammed for a while.
+// Queues can have several rules, e.g. pinging a second role once the queue grows even larger.
+#[derive(Debug, Clone)]
+pub struct ReminderRule {
+    pub id: i64,
+    pub queue: ChannelId,
+    pub fill_threshold: u64,
+    pub time_threshold: u64,
+    pub channel: Option<ChannelId>,
+    pub role: Option<RoleId>,
+    pub message: Option<String>,
+    pub embed: bool,
+    pub last_reminder: Option<Timestamp>,
+}
+
+impl ReminderRule {
+    pub fn new(
+        queue: ChannelId,
+        fill_threshold: u64,
+        time_threshold: u64,
+    ) -> Self {
+        Self {
+            id: 0,
+            queue,
+            fill_threshold,
+            time_threshold,
+            channel: None,
+            role: None,
+            message: None,
+            embed: false,
+            last_reminder: None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Over {} nations and no telegram for {} minutes",
+            self.fill_threshold, self.time_threshold
+        )
+    }
+
+    // Fills in the rule's message. Embeds leave the role out, as it's mentioned in the message content instead.
+    pub fn format_message(&self, values: &ReminderValues) -> String {
+        let template = self.message.as_deref().unwrap_or(DEFAULT_REMINDER_MESSAGE);
+        let role = self.role.filter(|_| !self.embed).map_or(String::new(), |role| role.mention().to_string());
+
+        let text = template
+            .replace("{role}", &role)
+            .replace("{region}", &prettify_name(&values.region))
+            .replace("{count}", &values.count.to_string())
+            .replace("{minutes}", &values.minutes.to_string())
+            .replace("{last_recruiter}", &values.last_recruiter.map_or(
+                "nobody".into(), |user| user.mention().to_string()
+            ))
+            .replace("{oldest_queued}", &values.oldest_queued.map_or(
+                "never".into(), |time| FormattedTimestamp::new(time, Some(FormattedTimestampStyle::RelativeTime)).to_string()
+            ));
+
+        // Messages that leave out the role would never ping it, so it goes in front instead
+        let text = if !template.contains("{role}") && !role.is_empty() {
+            format!("{} {}", role, text.trim())
+        } else {
+            text.trim().to_string()
+        };
+
+        // Placeholders can fill a message past the limit even though its template is within it
+        text.chars().take(MAX_REMINDER_TEXT_LENGTH).collect()
+    }
+
+    // Saves the rule, creating it if it's new, and fills in its id.
+    pub async fn insert(&mut self, pool: &sqlx::PgPool) {
+        let query = if self.id == 0 {
+            "INSERT INTO reminder_rules (queue, fill_threshold, time_threshold, ping_channel, ping_role, message, embed, last_reminder)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id"
+        } else {
+            "UPDATE reminder_rules SET queue = $1, fill_threshold = $2, time_threshold = $3, 
+                ping_channel = $4, ping_role = $5, message = $6, embed = $7, last_reminder = $8 WHERE id = $9 RETURNING id"
+        };
+
+        let mut query = sqlx::query_scalar::<_, i64>(query)
+            .bind(self.queue.get() as i64)
+            .bind(self.fill_threshold as i64)
+            .bind(self.time_threshold as i64)
+            .bind(self.channel.map(|v| v.get() as i64))
+            .bind(self.role.map(|v| v.get() as i64))
+            .bind(&self.message)
+            .bind(self.embed)
+            .bind(self.last_reminder.map(|v| v.timestamp()));
+
+        if self.id != 0 {
+            query = query.bind(self.id);
+        }
+
+        let result = query.fetch_one(pool).await;
+
+        match result {
+            Ok(id) => self.id = id,
+            Err(err) => warn!("Failed to save reminder rule '{:?}' to Postgres database - {:?}", self, err),
+        }
+    }
+
+    // Records when the rule last sent a reminder, so its cooldown carries over restarts.
+    pub async fn record_reminder(id: i64, time: Timestamp, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "UPDATE reminder_rules SET last_reminder = $1 WHERE id = $2"
+            ).bind(time.timestamp())
+            .bind(id)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to record reminder for rule '{:?}' in Postgres database - {:?}", id, result);
+        }
+    }
+
+    pub async fn remove(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "DELETE FROM reminder_rules WHERE id = $1"
+            ).bind(self.id)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete reminder rule '{:?}' from Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn remove_matching(
+        queue: i64,
+        pool: &sqlx::PgPool
+    ) {
+        let result = sqlx::query(
+           "DELETE FROM reminder_rules WHERE queue = $1"
+            ).bind(queue)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete reminder rules for queue '{:?}' from Postgres database - {:?}", queue, result);
+        }
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<ChannelId, Vec<ReminderRule>>, sqlx::Error> {
+        let vec = sqlx::query(
+            "SELECT id, queue, fill_threshold, time_threshold, ping_channel, ping_role, message, embed, last_reminder FROM reminder_rules ORDER BY id"
+        ).fetch_all(pool).await?;
+
+        let mut map: HashMap<ChannelId, Vec<ReminderRule>> = HashMap::new();
+        for value in vec {
+            let queue = ChannelId::new(value.get::<i64, &str>("queue") as u64);
+
+            map.entry(queue).or_default().push(ReminderRule {
+                id: value.get::<i64, &str>("id"),
+                queue,
+                fill_threshold: value.get::<i64, &str>("fill_threshold") as u64,
+                time_threshold: value.get::<i64, &str>("time_threshold") as u64,
+                channel: value.get::<Option<i64>, &str>("ping_channel").map(|v| ChannelId::new(v as u64)),
+                role: value.get::<Option<i64>, &str>("ping_role").map(|v| RoleId::new(v as u64)),
+                message: value.get::<Option<String>, &str>("message"),
+                embed: value.get::<bool, &str>("embed"),
+                last_reminder: value.get::<Option<i64>, &str>("last_reminder").and_then(|v| Timestamp::from_unix_timestamp(v).ok()),
+            });
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> ReminderValues {
+        ReminderValues {
+            region: "lazarus".into(),
+            count: 120,
+            minutes: 45,
+            last_recruiter: Some(UserId::new(200000000000000000)),
+            oldest_queued: None,
+        }
+    }
+
+    fn rule(message: &str, role: Option<u64>, embed: bool) -> ReminderRule {
+        let mut rule = ReminderRule::new(ChannelId::new(1), 50, 60);
+        rule.message = Some(message.into());
+        rule.role = role.map(RoleId::new);
+        rule.embed = embed;
+        rule
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        assert_eq!(
+            rule("{count} nations, {minutes} minutes, last sent by {last_recruiter}, oldest {oldest_queued}", None, false)
+                .format_message(&values()),
+            "120 nations, 45 minutes, last sent by <@200000000000000000>, oldest never"
+        );
+    }
+
+    #[test]
+    fn mentions_the_role_where_the_template_puts_it() {
+        assert_eq!(
+            rule("{count} nations queued, {role}!", Some(100), false).format_message(&values()),
+            "120 nations queued, <@&100>!"
+        );
+    }
+
+    #[test]
+    fn mentions_the_role_in_front_when_the_template_leaves_it_out() {
+        assert_eq!(
+            rule("{count} nations queued", Some(100), false).format_message(&values()),
+            "<@&100> 120 nations queued"
+        );
+    }
+
+    #[test]
+    fn leaves_the_role_out_of_embeds() {
+        assert_eq!(rule("{role} {count} nations queued", Some(100), true).format_message(&values()), "120 nations queued");
+        assert_eq!(rule("{count} nations queued", Some(100), true).format_message(&values()), "120 nations queued");
+    }
+
+    #[test]
+    fn leaves_the_role_empty_without_one() {
+        assert_eq!(rule("{role} {count} nations queued", None, false).format_message(&values()), "120 nations queued");
+    }
+
+    #[test]
+    fn truncates_long_messages() {
+        let message = rule(&"{last_recruiter}".repeat(200), None, false).format_message(&values());
+
+        assert_eq!(message.chars().count(), MAX_REMINDER_TEXT_LENGTH);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use log::warn;
 use serde::{Serialize, Deserialize};
 use serenity::all::{ChannelId, Timestamp, UserId};
@@ -16,6 +17,7 @@ pub struct ReportEntry {
     pub recruiter: u64,
     pub sender: String,
     pub template: String,
+    pub template_label: Option<String>,
     pub sent_time: i64,
     pub moved: bool,
     pub moved_time: Option<i64>,
@@ -31,6 +33,7 @@ impl ReportEntry {
         recruiter: UserId,
         sender: String,
         template: String,
+        template_label: String,
         sent_time: Timestamp,
     ) -> Self {
         Self {
@@ -42,6 +45,7 @@ impl ReportEntry {
             recruiter: recruiter.get(),
             sender,
             template,
+            template_label: Some(template_label),
             sent_time: sent_time.timestamp(),
             moved: false,
             moved_time: None
@@ -50,8 +54,8 @@ impl ReportEntry {
 
     pub async fn insert(&self, pool: &sqlx::PgPool) {
         let result = sqlx::query(
-           "INSERT INTO delivery_reports (name, event, origin, queue, queue_time, recruiter, sender, template, sent_time)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+           "INSERT INTO delivery_reports (name, event, origin, queue, queue_time, recruiter, sender, template, template_label, sent_time)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
             ).bind(&self.name)
             .bind(&self.event)
             .bind(&self.origin)
@@ -60,6 +64,7 @@ impl ReportEntry {
             .bind(self.recruiter as i64)
             .bind(&self.sender)
             .bind(&self.template)
+            .bind(&self.template_label)
             .bind(self.sent_time)
             .execute(pool).await;
 
@@ -68,18 +73,36 @@ impl ReportEntry {
         }
     }
 
+    // Latest telegram sent from each queue and who sent it.
+    pub async fn query_last_telegrams(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<ChannelId, (Timestamp, UserId)>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT ON (queue) queue, recruiter, sent_time FROM delivery_reports ORDER BY queue, sent_time DESC"
+        ).fetch_all(pool).await?;
+
+        Ok(rows.into_iter().filter_map(|row| {
+            let time = Timestamp::from_unix_timestamp(row.get::<i64, &str>("sent_time")).ok()?;
+
+            Some((
+                ChannelId::new(row.get::<i64, &str>("queue") as u64),
+                (time, UserId::new(row.get::<i64, &str>("recruiter") as u64))
+            ))
+        }).collect())
+    }
+
     pub async fn count(
         pool: &sqlx::PgPool,
-        queue: ChannelId,
+        queues: &[ChannelId],
         range: Option<(u64, u64)>
     ) -> Result<Vec<(String, usize)>, sqlx::Error> {
         let rows = if let Some((start, end)) = range {
             sqlx::query(
             "SELECT sender, COUNT(*) AS sender_count FROM delivery_reports
-                WHERE queue = $1 AND sent_time BETWEEN $2 AND $3
+                WHERE queue = ANY($1) AND sent_time BETWEEN $2 AND $3
                 GROUP BY sender ORDER BY sender_count DESC"
             )
-            .bind(queue.get() as i64)
+            .bind(queues.iter().map(|v| v.get() as i64).collect::<Vec<_>>())
             .bind(start as i64)
             .bind(end as i64)   
             .fetch_all(pool)
@@ -87,9 +110,9 @@ impl ReportEntry {
         } else {
             sqlx::query(
         "SELECT sender, COUNT(*) AS sender_count FROM delivery_reports
-            WHERE queue = $1 GROUP BY sender ORDER BY sender_count DESC"
+            WHERE queue = ANY($1) GROUP BY sender ORDER BY sender_count DESC"
             )
-            .bind(queue.get() as i64)
+            .bind(queues.iter().map(|v| v.get() as i64).collect::<Vec<_>>())
             .fetch_all(pool)
             .await?
         };
@@ -102,26 +125,99 @@ impl ReportEntry {
         ).collect())
     }
 
-    pub async fn query(
+    // Latest telegram sent to each of the given nations from each queue, by nation.
+    pub async fn query_last_sent(
+        pool: &sqlx::PgPool,
+        names: &[String],
+    ) -> Result<HashMap<String, HashMap<ChannelId, i64>>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT name, queue, MAX(sent_time) AS sent_time FROM delivery_reports WHERE name = ANY($1) GROUP BY name, queue"
+        ).bind(names)
+        .fetch_all(pool).await?;
+
+        let mut map: HashMap<String, HashMap<ChannelId, i64>> = HashMap::new();
+        for row in rows {
+            map.entry(row.get::<String, &str>("name")).or_default().insert(
+                ChannelId::new(row.get::<i64, &str>("queue") as u64), row.get::<i64, &str>("sent_time")
+            );
+        }
+
+        Ok(map)
+    }
+
+    // Amount of telegrams sent and nations that moved for each of the given templates in a queue.
+    pub async fn template_performance(
         pool: &sqlx::PgPool,
         queue: ChannelId,
+        templates: &[String],
+    ) -> Result<HashMap<String, (i64, i64)>, sqlx::Error> {
+        let rows = sqlx::query(
+        "SELECT template, COUNT(*) AS sent, COUNT(*) FILTER (WHERE moved) AS moved FROM delivery_reports
+            WHERE queue = $1 AND template = ANY($2) GROUP BY template"
+        )
+        .bind(queue.get() as i64)
+        .bind(templates)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(
+            |row| (
+                row.get::<String, &str>("template"),
+                (row.get::<i64, &str>("sent"), row.get::<i64, &str>("moved")),
+            )
+        ).collect())
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        queues: &[ChannelId],
         range: Option<(u64, u64)>
     ) -> Result<Vec<ReportEntry>, sqlx::Error> {
         if let Some((start, end)) = range {
             sqlx::query_as(
-        "SELECT name, event, origin, queue, queue_time, recruiter, sender, template, sent_time, moved, moved_time
-                FROM delivery_reports WHERE queue = $1 AND sent_time BETWEEN $2 AND $3"
-            ).bind(queue.get() as i64)
+        "SELECT name, event, origin, queue, queue_time, recruiter, sender, template, template_label, sent_time, moved, moved_time
+                FROM delivery_reports WHERE queue = ANY($1) AND sent_time BETWEEN $2 AND $3"
+            ).bind(queues.iter().map(|v| v.get() as i64).collect::<Vec<_>>())
             .bind(start as i64)
             .bind(end as i64)
             .fetch_all(pool).await
         } else {
             sqlx::query_as(
-        "SELECT name, event, origin, queue, queue_time, recruiter, sender, template, sent_time, moved, moved_time
-                FROM delivery_reports WHERE queue = $1"
-            ).bind(queue.get() as i64).fetch_all(pool).await
+        "SELECT name, event, origin, queue, queue_time, recruiter, sender, template, template_label, sent_time, moved, moved_time
+                FROM delivery_reports WHERE queue = ANY($1)"
+            ).bind(queues.iter().map(|v| v.get() as i64).collect::<Vec<_>>()).fetch_all(pool).await
         }
     }
+
+    // Writes the entries as a CSV report, leaving out the recruiter for entries from the listed queues.
+    pub fn write_csv(
+        entries: Vec<ReportEntry>,
+        hide_recruiters: &[ChannelId],
+    ) -> Result<Vec<u8>, crate::bot::Error> {
+        let mut output: Vec<u8> = Vec::new();
+        let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(&mut output);
+
+        writer.write_record([
+            "Nation Name", "Event Type", "Event Source", "Queued at Time", 
+            "Recruiter Discord ID", "Sender Nation", "Telegram Template",
+            "Template Label", "Sent at Time", "Moved to Region?", "Moved at Time"
+        ])?;
+
+        for entry in entries {
+            if hide_recruiters.contains(&ChannelId::new(entry.queue)) {
+                writer.serialize((
+                    &entry.name, &entry.event, &entry.origin, entry.queue_time, None::<u64>, &entry.sender,
+                    &entry.template, &entry.template_label, entry.sent_time, entry.moved, entry.moved_time
+                ))?;
+            } else {
+                writer.serialize(entry)?;
+            }
+        }
+
+        drop(writer);
+
+        Ok(output)
+    }
     
     pub async fn mark_move(
         pool: &sqlx::PgPool,
@@ -1,11 +1,11 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 use log::warn;
-use rand::seq::IndexedRandom;
-use serenity::all::{CacheHttp, CreateMessage, Context, UserId, ChannelId, Timestamp};
+use serenity::all::{CacheHttp, CreateMessage, Context, Mentionable, UserId, ChannelId, Timestamp};
+use sqlx::Row;
 
 use crate::api::calculate_telegram_delay;
-use crate::embeds::create_pause_embed;
-use crate::{embeds::create_telegram_embed, models::report::ReportEntry};
+use crate::embeds::{create_pause_embed, create_session_restored_embed};
+use crate::{embeds::create_telegram_embed, models::{queue::Queue, report::ReportEntry}};
 use crate::bot::{Data, Error};
 
 #[derive(Debug, Clone)]
@@ -14,7 +14,7 @@ pub enum RecruitDelay {
     Automatic,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Session {
     pub user: UserId,
     pub queue: ChannelId,
@@ -35,6 +35,84 @@ impl fmt::Display for RecruitDelay {
 }
 
 impl Session {
+    pub async fn insert(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "INSERT INTO sessions (queue, user_id, delay, last_activity_check, pause_time)
+                VALUES ($1, $2, $3, $4, $5) ON CONFLICT (queue, user_id) DO UPDATE
+                SET delay = EXCLUDED.delay,
+                last_activity_check = EXCLUDED.last_activity_check,
+                pause_time = EXCLUDED.pause_time"
+            ).bind(self.queue.get() as i64)
+            .bind(self.user.get() as i64)
+            .bind(match self.delay {
+                RecruitDelay::Fixed(delay) => Some(delay as i64),
+                RecruitDelay::Automatic => None,
+            })
+            .bind(self.last_activity_check.timestamp())
+            .bind(self.pause_time.map(|v| v.timestamp()))
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to save session '{:?}' to Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn remove(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "DELETE FROM sessions WHERE queue = $1 AND user_id = $2"
+            ).bind(self.queue.get() as i64)
+            .bind(self.user.get() as i64)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete session '{:?}' from Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn remove_matching(
+        queue: i64,
+        pool: &sqlx::PgPool
+    ) {
+        let result = sqlx::query(
+           "DELETE FROM sessions WHERE queue = $1"
+            ).bind(queue)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete sessions for queue '{:?}' from Postgres database - {:?}", queue, result);
+        }
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<(ChannelId, UserId), Session>, sqlx::Error> {
+        let vec = sqlx::query(
+            "SELECT queue, user_id, delay, last_activity_check, pause_time FROM sessions"
+        ).fetch_all(pool).await?;
+
+        let mut map = HashMap::new();
+        for value in vec {
+            let queue = ChannelId::new(value.get::<i64, &str>("queue") as u64);
+            let user = UserId::new(value.get::<i64, &str>("user_id") as u64);
+
+            map.insert((queue, user), Session {
+                user,
+                queue,
+                delay: value.get::<Option<i64>, &str>("delay").map_or(
+                    RecruitDelay::Automatic, |v| RecruitDelay::Fixed(v as u64)
+                ),
+                last_activity_check: Timestamp::from_unix_timestamp(
+                    value.get::<i64, &str>("last_activity_check")
+                ).unwrap_or_else(|_| Timestamp::now()),
+                pause_time: value.get::<Option<i64>, &str>("pause_time").and_then(
+                    |v| Timestamp::from_unix_timestamp(v).ok()
+                ),
+            });
+        }
+
+        Ok(map)
+    }
+
     pub async fn try_send_new_telegram(
         &self, ctx: &Context, data: &Data
     ) -> Result<(), Error> {
@@ -49,11 +127,11 @@ impl Session {
             }
         };
 
-        let sessions = data.inner.sessions.lock().await.values().filter_map(|s| {
-            if s.queue == self.queue { Some(s.user) } else { None }
+        let sessions = data.inner.sessions.lock().await.values().map(|s| {
+            (s.queue, s.user)
         }).collect::<Vec<_>>();
 
-        let ((nations, templates, update), channel) = {
+        let ((nations, templates, updates), channel) = {
             let mut queues = data.inner.queues.lock().await;
             let queue = match queues.get_mut(&self.queue) {
                 Some(v) => v,
@@ -66,7 +144,18 @@ impl Session {
                 },
             };
 
-            (queue.pull(&user_data, 8, sessions), queue.channel)
+            let batch_size = queue.batch_size_for(&user_data);
+            let channel = queue.channel;
+
+            let (nations, templates, update) = queue.pull(
+                &user_data, batch_size, sessions.iter().filter_map(|v| if v.0 == channel { Some(v.1) } else { None }).collect(), &data.inner.pool
+            ).await;
+
+            let group_updates = Queue::remove_from_group(
+                &mut queues, channel, &nations, &sessions, &data.inner.pool
+            ).await;
+
+            ((nations, templates, update.into_iter().chain(group_updates).collect::<Vec<_>>()), channel)
         };
 
         if nations.is_empty() || templates.is_empty() {
@@ -74,12 +163,11 @@ impl Session {
             return Ok(());
         }
 
-        let template = {
-            let mut rng = rand::rng();
-            templates.choose(&mut rng).expect(
-                "Template list should not be empty"
-            )
-        };
+        let template = user_data.choose_template(
+            &templates, channel, &data.inner.pool
+        ).await.expect(
+            "Template list should not be empty"
+        );
 
         let cooldown = match self.delay {
             RecruitDelay::Fixed(time) => {
@@ -93,11 +181,11 @@ impl Session {
         };
 
         let (embed, components) = create_telegram_embed(
-            &nations, template, &user_data.nation, cooldown, &data.inner.user_agent, true
+            &nations, &template, &user_data.nation, cooldown, &data.inner.user_agent, Some(self.queue)
         );
 
         data.inner.cooldowns.lock().await.insert(
-            UserId::new(user_data.user_id), 
+            user_data.nation.clone(), 
             (cooldown, None)
         );
 
@@ -105,7 +193,7 @@ impl Session {
             ctx.http(), CreateMessage::new().embed(embed).components(components)
         ).await?;
 
-        if let Some(update) = update {
+        for update in updates {
             update.execute(ctx.clone()).await;
         }
 
@@ -118,7 +206,8 @@ impl Session {
                 nation.queue_time, 
                 self.user,
                 user_data.nation.clone(),
-                template.clone(),
+                template.code.clone(),
+                template.label.clone(),
                 Timestamp::now()
             ).insert(&data.inner.pool).await;
         }
@@ -129,7 +218,7 @@ impl Session {
     pub async fn inactivity_pause(
         &self, ctx: &Context
     ) -> Result<(), Error> {
-        let (embed, components) = create_pause_embed();
+        let (embed, components) = create_pause_embed(self.queue);
 
         self.user.direct_message(
             ctx.http(), CreateMessage::new().embed(embed).components(components)
@@ -147,4 +236,34 @@ impl Session {
 
         Ok(())
     }
+
+    pub async fn restore_continue(
+        &self, ctx: &Context
+    ) -> Result<(), Error> {
+        // Paused sessions stay paused, so the recruiter needs the Continue button again
+        let (embed, components) = if self.pause_time.is_some() {
+            create_pause_embed(self.queue)
+        } else {
+            create_session_restored_embed(&self.delay, self.queue)
+        };
+
+        self.user.direct_message(
+            ctx.http(), CreateMessage::new().embed(embed).components(components)
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn restore_close(
+        &self, ctx: &Context
+    ) -> Result<(), Error> {
+        self.user.direct_message(
+            ctx.http(), CreateMessage::new().content(format!(
+                "Vanille was restarted, and your session for {} was closed because it had been inactive for too long.", 
+                self.queue.mention()
+            ))
+        ).await?;
+
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,176 @@
+use std::{collections::HashMap, fmt};
+use lazy_static::lazy_static;
+use log::warn;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use serenity::all::{ChannelId, UserId};
+use sqlx::Row;
+
+pub const DEFAULT_TEMPLATE_WEIGHT: u32 = 1;
+pub const MAX_TEMPLATE_WEIGHT: u32 = 100;
+
+lazy_static! {
+    static ref TEMPLATE_RE: Regex = Regex::new(r#"^%TEMPLATE-[0-9]+%$"#).unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TemplateCategory {
+    Newfound,
+    Refound,
+    Both,
+}
+
+impl TemplateCategory {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "newfound" => Some(Self::Newfound),
+            "refound" => Some(Self::Refound),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Newfound => "newfound",
+            Self::Refound => "refound",
+            Self::Both => "both",
+        }
+    }
+
+    // Whether this template can be used for nations queued with the given event ("newfound" or "refound").
+    pub fn matches(&self, event: &str) -> bool {
+        match self {
+            Self::Both => true,
+            _ => self.as_str() == event,
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        if self == other { self } else { Self::Both }
+    }
+}
+
+impl fmt::Display for TemplateCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub id: i64,
+    pub code: String,
+    pub label: String,
+    pub category: TemplateCategory,
+    pub enabled: bool,
+    pub weight: u32,
+}
+
+impl Template {
+    pub fn new(
+        code: String,
+        label: String,
+        category: TemplateCategory,
+        weight: u32,
+    ) -> Self {
+        Self {
+            id: 0,
+            code,
+            label,
+            category,
+            enabled: true,
+            weight,
+        }
+    }
+
+    // Only checks the code's format.
+    pub fn is_valid_code(code: &str) -> bool {
+        TEMPLATE_RE.is_match(code)
+    }
+
+    // Saves the template, replacing any template with the same code, and fills in its id.
+    pub async fn insert(&mut self, queue: ChannelId, user: UserId, pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+        let result = sqlx::query_scalar::<_, i64>(
+           "INSERT INTO templates (queue, user_id, code, label, category, enabled, weight)
+                VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (queue, user_id, code) DO UPDATE
+                SET label = EXCLUDED.label,
+                category = EXCLUDED.category,
+                enabled = EXCLUDED.enabled,
+                weight = EXCLUDED.weight
+                RETURNING id"
+            ).bind(queue.get() as i64)
+            .bind(user.get() as i64)
+            .bind(&self.code)
+            .bind(&self.label)
+            .bind(self.category.as_str())
+            .bind(self.enabled)
+            .bind(self.weight as i32)
+            .fetch_one(pool).await;
+
+        match result {
+            Ok(id) => {
+                self.id = id;
+                Ok(())
+            },
+            Err(err) => {
+                warn!("Failed to save template '{:?}' to Postgres database - {:?}", self, err);
+                Err(err)
+            },
+        }
+    }
+
+    pub async fn remove(&self, pool: &sqlx::PgPool) {
+        let result = sqlx::query(
+           "DELETE FROM templates WHERE id = $1"
+            ).bind(self.id)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete template '{:?}' from Postgres database - {:?}", self, result);
+        }
+    }
+
+    pub async fn remove_matching(
+        queue: i64,
+        pool: &sqlx::PgPool
+    ) {
+        let result = sqlx::query(
+           "DELETE FROM templates WHERE queue = $1"
+            ).bind(queue)
+            .execute(pool).await;
+
+        if result.is_err() {
+            warn!("Failed to delete templates for queue '{:?}' from Postgres database - {:?}", queue, result);
+        }
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+    ) -> Result<HashMap<(ChannelId, UserId), Vec<Template>>, sqlx::Error> {
+        let vec = sqlx::query(
+            "SELECT id, queue, user_id, code, label, category, enabled, weight FROM templates ORDER BY id"
+        ).fetch_all(pool).await?;
+
+        let mut map: HashMap<(ChannelId, UserId), Vec<Template>> = HashMap::new();
+        for value in vec {
+            let key = (
+                ChannelId::new(value.get::<i64, &str>("queue") as u64),
+                UserId::new(value.get::<i64, &str>("user_id") as u64)
+            );
+
+            map.entry(key).or_default().push(Template {
+                id: value.get::<i64, &str>("id"),
+                code: value.get::<String, &str>("code"),
+                label: value.get::<String, &str>("label"),
+                category: TemplateCategory::parse(
+                    &value.get::<String, &str>("category")
+                ).unwrap_or(TemplateCategory::Both),
+                enabled: value.get::<bool, &str>("enabled"),
+                weight: value.get::<i32, &str>("weight") as u32,
+            });
+        }
+
+        Ok(map)
+    }
+}
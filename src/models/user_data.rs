@@ -1,9 +1,16 @@
 use std::collections::HashMap;
+use rand::{Rng, seq::IndexedRandom};
 use serde::{Serialize, Deserialize};
 use serenity::all::{ChannelId, UserId};
 use sqlx::FromRow;
 use log::warn;
 
+use crate::models::{report::ReportEntry, template::Template};
+
+// Share of adaptive picks that ignore statistics and fall back to weighted random selection,
+// so that every template keeps receiving telegrams and its move rate stays up to date.
+const EXPLORATION_RATE: f64 = 0.2;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserData {
     #[sqlx(try_from = "i64")]
@@ -12,8 +19,11 @@ pub struct UserData {
     pub user_id: u64,
     pub nation: String,
     pub founded: i64,
-    pub newfounds: Vec<String>,
-    pub refounds: Vec<String>,
+    pub batch_size: Option<i64>,
+    pub adaptive: bool,
+    pub suspended: bool,
+    #[sqlx(skip)]
+    pub templates: Vec<Template>,
 }
 
 impl UserData {
@@ -22,33 +32,38 @@ impl UserData {
         user: UserId, 
         nation: String,
         founded: i64,
-        newfounds: Vec<String>,
-        refounds: Vec<String>,
+        batch_size: Option<i64>,
+        adaptive: bool,
+        templates: Vec<Template>,
     ) -> Self {
         Self {
             queue: queue.get(),
             user_id: user.get(),
             nation,
             founded,
-            newfounds,
-            refounds
+            batch_size,
+            adaptive,
+            suspended: false,
+            templates,
         }
     }
 
     pub async fn insert(&self, pool: &sqlx::PgPool) {
         let result = sqlx::query(
-           "INSERT INTO user_data (queue, user_id, nation, founded, newfounds, refounds)
-                VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (queue, user_id) DO UPDATE
+           "INSERT INTO user_data (queue, user_id, nation, founded, batch_size, adaptive, suspended)
+                VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (queue, user_id) DO UPDATE
                 SET nation = EXCLUDED.nation,
                 founded = EXCLUDED.founded,
-                newfounds = EXCLUDED.newfounds,
-                refounds = EXCLUDED.refounds"
+                batch_size = EXCLUDED.batch_size,
+                adaptive = EXCLUDED.adaptive,
+                suspended = EXCLUDED.suspended"
             ).bind(self.queue as i64)
             .bind(self.user_id as i64)
             .bind(&self.nation)
             .bind(&self.founded)
-            .bind(&self.newfounds)
-            .bind(&self.refounds)
+            .bind(self.batch_size)
+            .bind(self.adaptive)
+            .bind(self.suspended)
             .execute(pool).await;
 
         if result.is_err() {
@@ -68,13 +83,15 @@ impl UserData {
         if result.is_err() {
             warn!("Failed to delete user data for queue '{:?}' from Postgres database - {:?}", queue, result);
         }
+
+        Template::remove_matching(queue, pool).await;
     }
 
     pub async fn query(
         pool: &sqlx::PgPool,
     ) -> Result<HashMap<(ChannelId, UserId), UserData>, sqlx::Error> {
         let vec = sqlx::query_as::<_, UserData>(
-            "SELECT queue, user_id, nation, founded, newfounds, refounds FROM user_data"
+            "SELECT queue, user_id, nation, founded, batch_size, adaptive, suspended FROM user_data"
         ).fetch_all(pool).await?;
 
         let mut map = HashMap::new();
@@ -82,6 +99,52 @@ impl UserData {
             map.insert((ChannelId::new(value.queue), UserId::new(value.user_id)), value);
         }
 
+        for (key, templates) in Template::query(pool).await? {
+            if let Some(data) = map.get_mut(&key) {
+                data.templates = templates;
+            }
+        }
+
         Ok(map)
     }
-}
\ No newline at end of file
+
+    // Enabled templates that can be used for nations queued with the given event.
+    pub fn templates_for(&self, event: &str) -> Vec<&Template> {
+        self.templates.iter().filter(|v| v.enabled && v.category.matches(event)).collect()
+    }
+
+    // Picks a template out of the eligible ones for a batch. Templates are picked at random according to
+    // their weights, unless adaptive selection is enabled, in which case the template with the best
+    // move rate in this queue is usually picked instead.
+    pub async fn choose_template(
+        &self, templates: &[Template], queue: ChannelId, pool: &sqlx::PgPool
+    ) -> Option<Template> {
+        let performance = if self.adaptive {
+            let codes = templates.iter().map(|v| v.code.clone()).collect::<Vec<_>>();
+
+            match ReportEntry::template_performance(pool, queue, &codes).await {
+                Ok(performance) => Some(performance),
+                Err(err) => {
+                    warn!("Failed to query template performance for queue {}: {}", queue, err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut rng = rand::rng();
+
+        if let Some(performance) = performance && !rng.random_bool(EXPLORATION_RATE) {
+            // Smoothed move rate, so templates with few telegrams sent aren't judged on a handful of results
+            let score = |template: &Template| {
+                let (sent, moved) = performance.get(&template.code).copied().unwrap_or((0, 0));
+                (moved + 1) as f64 / (sent + 2) as f64
+            };
+
+            return templates.iter().max_by(|a, b| score(a).total_cmp(&score(b))).cloned();
+        }
+
+        templates.choose_weighted(&mut rng, |template| template.weight).ok().cloned()
+    }
+}
@@ -1,66 +1,323 @@
-use std::process::exit;
-use serenity::all::Context;
+use std::{collections::{HashMap, HashSet}, time::Duration};
+use serenity::all::{ChannelId, Context, Timestamp};
 use futures::future;
-use log::error;
+use log::{info, warn};
 
 use caramel::akari;
 
+use crate::api::query_founding_events;
 use crate::bot::Data;
-use crate::models::report::ReportEntry;
+use crate::feed;
+use crate::models::{queue::{Queue, QueueEvent}, report::ReportEntry};
+use super::residency::suspend_nation;
+
+const INITIAL_RECONNECT_DELAY: u64 = 5;
+const MAX_RECONNECT_DELAY: u64 = 5 * 60; // Back off up to 5 minutes between reconnection attempts
+
+async fn connect(data: &Data) -> Result<(lapin::Connection, lapin::Channel), lapin::Error> {
+    let conn = lapin::Connection::connect(
+        &data.inner.rabbitmq_url,
+        lapin::ConnectionProperties::default(),
+    ).await?;
+
+    let channel = conn.create_channel().await?;
+
+    Ok((conn, channel))
+}
 
 pub async fn akari_task(ctx: Context, data: Data) {
-    let mut consumer = akari::create_consumer(
-        &data.inner.channel, &data.inner.config.input.exchange_name, Some(vec!["nfound", "nrefound", "move"])
-    ).await.unwrap_or_else(|err| {
-        error!("Failed to create Akari consumer: {}", err);
-        exit(1);
-    });
-
-    while let Some(event) = akari::consume(&mut consumer).await {
-        match event.category.as_str() {
-            "nfound" | "nrefound" => {
-                let nation = event.actor.expect(&format!("{} event doesn't have a nation", event.category));
-                let region = event.origin.expect(&format!("{} event doesn't have a region", event.category));
-
-                let sessions = data.inner.sessions.lock().await.values().map(|s| {
-                    (s.queue, s.user)
-                }).collect::<Vec<_>>();
-
-                let queue_updates = {
-                    let mut queues = data.inner.queues.lock().await;
-
-                    queues.values_mut().flat_map(|queue| {
-                        queue.add_to_queue(&nation, match event.category.as_str() {
-                            "nfound" => "newfound",
-                            "nrefound" => "refound",
-                            _ => unreachable!(),
-                        }, &region, sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1)} else { None }).collect())
-                    }).collect::<Vec<_>>()
-                };
+    let mut delay = INITIAL_RECONNECT_DELAY;
+
+    loop {
+        let (_conn, channel) = match connect(&data).await {
+            Ok(v) => v,
+            Err(err) => {
+                warn!("Failed to connect to RabbitMQ, retrying in {} seconds: {}", delay, err);
+                reconnect_after(&ctx, &data, &mut delay).await;
+                continue;
+            }
+        };
+
+        let mut consumer = match akari::create_consumer(
+            &channel, &data.inner.config.input.exchange_name, Some(vec!["nfound", "nrefound", "move", "cte"])
+        ).await {
+            Ok(v) => v,
+            Err(err) => {
+                warn!("Failed to create Akari consumer, retrying in {} seconds: {}", delay, err);
+                reconnect_after(&ctx, &data, &mut delay).await;
+                continue;
+            }
+        };
+
+        info!("Connected to Akari event feed");
+        delay = INITIAL_RECONNECT_DELAY;
+
+        if feed::set_connected() {
+            refresh_queue_embeds(&ctx, &data).await;
+        }
+
+        // Live events are buffered by the consumer while this runs, so nothing is lost in between.
+        // After a reconnection, this also catches up on nations founded while the feed was down.
+        backfill_queues(&ctx, &data).await;
+
+        while let Some(event) = akari::consume(&mut consumer).await {
+            match event.category.as_str() {
+                "nfound" | "nrefound" => {
+                    let (Some(nation), Some(region)) = (
+                        event.actor.filter(|v| !v.is_empty()), event.origin.filter(|v| !v.is_empty())
+                    ) else {
+                        reject_event(&event.category, "missing nation or region");
+                        continue;
+                    };
+
+                    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+                        (s.queue, s.user)
+                    }).collect::<Vec<_>>();
+
+                    // Newly founded nations can't have been sent a telegram before
+                    let sent = if event.category == "nrefound" {
+                        query_last_sent(&data.inner.pool, std::slice::from_ref(&nation)).await.remove(&nation).unwrap_or_default()
+                    } else {
+                        HashMap::new()
+                    };
+
+                    let queue_updates = {
+                        let mut queues = data.inner.queues.lock().await;
+                        let last_sent = Queue::group_last_sent(&queues, &sent);
+                        let mut updates = Vec::new();
+
+                        for queue in queues.values_mut() {
+                            updates.extend(queue.add_to_queue(QueueEvent {
+                                nation: &nation,
+                                event: match event.category.as_str() {
+                                    "nfound" => "newfound",
+                                    "nrefound" => "refound",
+                                    _ => unreachable!(),
+                                },
+                                region: &region,
+                                queue_time: Timestamp::now(),
+                                last_sent: last_sent.get(&queue.channel).copied(),
+                            }, sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1)} else { None }).collect(), &data.inner.pool).await);
+                        }
+
+                        updates
+                    };
+
+                    future::join_all(queue_updates.into_iter().map(async |update| {
+                        update.execute(ctx.clone()).await;
+                    })).await;
+                },
+                "move" => {
+                    let (Some(nation), Some(region)) = (
+                        event.actor.filter(|v| !v.is_empty()), event.destination.filter(|v| !v.is_empty())
+                    ) else {
+                        reject_event(&event.category, "missing nation or destination region");
+                        continue;
+                    };
+
+                    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+                        (s.queue, s.user)
+                    }).collect::<Vec<_>>();
+
+                    let (channels, queue_updates) = {
+                        let mut queues = data.inner.queues.lock().await;
+                        let mut channels = Vec::new();
+                        let mut updates = Vec::new();
+
+                        for queue in queues.values_mut() {
+                            if region == queue.region {
+                                channels.push(queue.channel);
+                            }
+
+                            // Nations that already joined the region don't need a telegram anymore
+                            if region == queue.region || queue.drop_moved {
+                                updates.extend(queue.remove_nation(
+                                    &nation, sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1)} else { None }).collect(), &data.inner.pool
+                                ).await);
+                            }
+                        }
+
+                        (channels, updates)
+                    };
+
+                    for queue in channels {
+                        ReportEntry::mark_move(&data.inner.pool, queue, &nation, event.time).await;
+                    }
+
+                    future::join_all(queue_updates.into_iter().map(async |update| {
+                        update.execute(ctx.clone()).await;
+                    })).await;
+                },
+                "cte" => {
+                    let Some(nation) = event.actor.filter(|v| !v.is_empty()) else {
+                        reject_event(&event.category, "missing nation");
+                        continue;
+                    };
+
+                    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+                        (s.queue, s.user)
+                    }).collect::<Vec<_>>();
+
+                    let queue_updates = {
+                        let mut queues = data.inner.queues.lock().await;
+                        let mut updates = Vec::new();
+
+                        for queue in queues.values_mut() {
+                            updates.extend(queue.remove_nation(
+                                &nation, sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1)} else { None }).collect(), &data.inner.pool
+                            ).await);
+                        }
+
+                        updates
+                    };
+
+                    future::join_all(queue_updates.into_iter().map(async |update| {
+                        update.execute(ctx.clone()).await;
+                    })).await;
+
+                    suspend_nation(&ctx, &data, &nation, "ceased to exist").await;
+                },
+                _ => ()
+            }
+        }
 
-                future::join_all(queue_updates.into_iter().map(async |update| {
-                    update.execute(ctx.clone()).await;
-                })).await;
-            },
-            "move" => {
-                let nation = event.actor.expect(&format!("{} event doesn't have a nation", event.category));
-                let region = event.destination.expect(&format!("{} event doesn't have a region", event.category));
-
-                let channels = {
-                    let queues = data.inner.queues.lock().await;
-
-                    queues.values().flat_map(|queue| {
-                        if region == queue.region {
-                            Some(queue.channel)
-                        } else { None }
-                    }).collect::<Vec<_>>()
+        warn!("Akari event feed closed, reconnecting in {} seconds", delay);
+        reconnect_after(&ctx, &data, &mut delay).await;
+    }
+}
+
+fn reject_event(category: &str, reason: &str) {
+    warn!("Skipping malformed {} event: {}", category, reason);
+    feed::record_rejected_event();
+}
+
+// Marks the feed as disconnected and waits before the next attempt, doubling the delay each time.
+async fn reconnect_after(ctx: &Context, data: &Data, delay: &mut u64) {
+    if feed::set_disconnected() {
+        refresh_queue_embeds(ctx, data).await;
+    }
+
+    tokio::time::sleep(Duration::from_secs(*delay)).await;
+    *delay = (*delay * 2).min(MAX_RECONNECT_DELAY);
+}
+
+// Shows the current event feed status on every queue embed.
+async fn refresh_queue_embeds(ctx: &Context, data: &Data) {
+    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+        (s.queue, s.user)
+    }).collect::<Vec<_>>();
+
+    let queue_updates = data.inner.queues.lock().await.values().map(|queue| {
+        queue.generate_queue_update(
+            sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1) } else { None }).collect()
+        )
+    }).collect::<Vec<_>>();
+
+    future::join_all(queue_updates.into_iter().map(async |update| {
+        update.execute(ctx.clone()).await;
+    })).await;
+}
+
+// Latest telegram sent to each of the nations from each queue, used to skip nations that were recently
+// sent a telegram. Nations are queued anyway if the query fails.
+async fn query_last_sent(pool: &sqlx::PgPool, names: &[String]) -> HashMap<String, HashMap<ChannelId, i64>> {
+    if names.is_empty() {
+        return HashMap::new();
+    }
+
+    match ReportEntry::query_last_sent(pool, names).await {
+        Ok(sent) => sent,
+        Err(err) => {
+            warn!("Failed to check previous telegrams to {}, queueing anyway: {}", names.join(", "), err);
+            HashMap::new()
+        }
+    }
+}
+
+// Catches up on nations founded while the bot was offline, using the NationStates happenings API.
+// Each queue only receives events newer than the last nation it saw, and eviction keeps it within its size.
+async fn backfill_queues(ctx: &Context, data: &Data) {
+    let cutoffs = {
+        let queues = data.inner.queues.lock().await;
+        let mut cutoffs: HashMap<ChannelId, Option<i64>> = HashMap::new();
+
+        for queue in queues.values() {
+            match queue.query_last_queue_time(&data.inner.pool).await {
+                Ok(time) => { cutoffs.insert(queue.channel, time); },
+                Err(err) => warn!("Failed to query last queue time for {}, skipping backfill: {}", queue.channel, err),
+            }
+        }
+
+        cutoffs
+    };
+
+    if cutoffs.is_empty() {
+        return;
+    }
+
+    let since = if cutoffs.values().any(|v| v.is_none()) {
+        None
+    } else {
+        cutoffs.values().flatten().min().copied()
+    };
+
+    let events = match query_founding_events(&data.inner.api_client, since).await {
+        Ok(events) => events,
+        Err(err) => {
+            warn!("Failed to query founding events for backfill: {}", err);
+            return;
+        }
+    };
+
+    let sessions = data.inner.sessions.lock().await.values().map(|s| {
+        (s.queue, s.user)
+    }).collect::<Vec<_>>();
+
+    let refounds = events.iter().filter(|v| v.event == "refound").map(|v| v.nation.clone()).collect::<Vec<_>>();
+    let mut sent = query_last_sent(&data.inner.pool, &refounds).await;
+
+    let queue_updates = {
+        let mut queues = data.inner.queues.lock().await;
+        let mut updated = HashSet::new();
+
+        for event in &events {
+            let Ok(queue_time) = Timestamp::from_unix_timestamp(event.time) else {
+                continue;
+            };
+
+            let last_sent = Queue::group_last_sent(&queues, &sent.remove(&event.nation).unwrap_or_default());
+
+            for queue in queues.values_mut() {
+                let Some(cutoff) = cutoffs.get(&queue.channel) else {
+                    continue;
                 };
 
-                for queue in channels {
-                    ReportEntry::mark_move(&data.inner.pool, queue, &nation, event.time).await;
+                if cutoff.is_some_and(|v| event.time <= v) {
+                    continue;
+                }
+
+                if queue.add_to_queue(QueueEvent {
+                    nation: &event.nation,
+                    event: &event.event,
+                    region: &event.region,
+                    queue_time,
+                    last_sent: last_sent.get(&queue.channel).copied(),
+                }, vec![], &data.inner.pool).await.is_some() {
+                    updated.insert(queue.channel);
                 }
             }
-            _ => ()
         }
-    }
-}
\ No newline at end of file
+
+        info!("Backfilled {} founding events into {} queues", events.len(), updated.len());
+
+        queues.values().filter(|queue| updated.contains(&queue.channel)).map(|queue| {
+            queue.generate_queue_update(
+                sessions.iter().filter_map(|v| if v.0 == queue.channel { Some(v.1) } else { None }).collect()
+            )
+        }).collect::<Vec<_>>()
+    };
+
+    future::join_all(queue_updates.into_iter().map(async |update| {
+        update.execute(ctx.clone()).await;
+    })).await;
+}
@@ -1,9 +1,10 @@
 use std::{collections::{HashSet, HashMap}, time::Duration};
-use serenity::all::{CacheHttp, Context, ComponentInteraction, UserId, Timestamp};
+use serenity::all::{CacheHttp, Context, ComponentInteraction, Timestamp};
 use rand::seq::SliceRandom;
 use log::warn;
 
 use crate::bot::Data;
+use crate::models::session::SESSION_TELEGRAM_BUFFER;
 
 #[derive(PartialEq)]
 enum SessionAction {
@@ -15,11 +16,59 @@ enum SessionAction {
 const SESSION_PAUSE_DELAY: i64 = 20 * 60; // Session is paused 10 min after the last activity check is passed
 const INACTIVITY_CLOSE_DELAY: i64 = 5 * 60; // Session is closed 5 min after the last activity check is presented and not responded to
 
+// Sessions restored from the database are closed if they would have been closed for inactivity
+// while the bot was offline, and continued otherwise. Either way, the recruiter gets a DM about it,
+// which is the pause prompt again for sessions that were paused.
+async fn restore_sessions(ctx: &Context, data: &Data) {
+    let now = Timestamp::now().timestamp();
+
+    let (continued, closed) = {
+        let mut sessions = data.inner.sessions.lock().await;
+
+        let expired = sessions.values().filter(|session| match session.pause_time {
+            Some(pause_time) => (now - pause_time.timestamp()) > INACTIVITY_CLOSE_DELAY,
+            None => (now - session.last_activity_check.timestamp()) > SESSION_PAUSE_DELAY + INACTIVITY_CLOSE_DELAY,
+        }).map(|session| (session.queue, session.user)).collect::<Vec<_>>();
+
+        let closed = expired.into_iter().flat_map(|key| sessions.remove(&key)).collect::<Vec<_>>();
+
+        (sessions.values().cloned().collect::<Vec<_>>(), closed)
+    };
+
+    {
+        // Cooldowns aren't persisted, so give each continued session the same buffer as a new one
+        let user_data = data.inner.user_data.lock().await;
+        let mut cooldowns = data.inner.cooldowns.lock().await;
+        let cooldown = now + SESSION_TELEGRAM_BUFFER;
+
+        for session in &continued {
+            if let Some(user_data) = user_data.get(&(session.queue, session.user)) {
+                cooldowns.entry(user_data.nation.clone()).or_insert((cooldown, None));
+            }
+        }
+    }
+
+    for session in closed {
+        session.remove(&data.inner.pool).await;
+        session.restore_close(ctx).await.unwrap_or_else(|err| {
+            warn!("Error notifying user of restored session close: {err}");
+        });
+    }
+
+    for session in continued {
+        session.restore_continue(ctx).await.unwrap_or_else(|err| {
+            warn!("Error notifying user of restored session: {err}");
+        });
+    }
+}
+
 pub async fn cooldown_task(ctx: Context, data: Data) {
+    restore_sessions(&ctx, &data).await;
+
     let mut ticker = tokio::time::interval(Duration::from_secs(1));
 
-    let mut extant_cooldowns: HashSet<UserId> = HashSet::new();
-    let mut expired_cooldowns: HashMap<UserId, Option<ComponentInteraction>> = HashMap::new();
+    let mut extant_cooldowns: HashSet<String> = HashSet::new();
+    let mut expired_cooldowns: HashMap<String, Option<ComponentInteraction>> = HashMap::new();
 
     loop {
         ticker.tick().await;
@@ -52,8 +101,16 @@ pub async fn cooldown_task(ctx: Context, data: Data) {
 
         let sessions_to_update = {
             let mut sessions = data.inner.sessions.lock().await;
+            let user_data = data.inner.user_data.lock().await;
+
+            // Sessions sharing a sender nation also share its cooldown, so only one of them may send per tick
+            let mut claimed_nations: HashSet<String> = HashSet::new();
+
+            let mut rng = rand::rng();
+            let mut candidates = sessions.values_mut().collect::<Vec<_>>();
+            candidates.shuffle(&mut rng);
 
-            let mut result = sessions.values_mut().flat_map(|session| {
+            let result = candidates.into_iter().flat_map(|session| {
                 if let Some(pause_time) = session.pause_time {
                     if (Timestamp::now().timestamp() - pause_time.timestamp()) > INACTIVITY_CLOSE_DELAY {
                         // Session activity check expired
@@ -63,7 +120,9 @@ pub async fn cooldown_task(ctx: Context, data: Data) {
                         None
                     }
                 } else {
-                    if extant_cooldowns.contains(&session.user) { 
+                    let nation = user_data.get(&(session.queue, session.user)).map(|v| &v.nation);
+
+                    if nation.is_some_and(|v| extant_cooldowns.contains(v) || claimed_nations.contains(v)) { 
                         // Cooldown still in progress
                         None
                     } else {
@@ -73,6 +132,10 @@ pub async fn cooldown_task(ctx: Context, data: Data) {
                             Some((session.clone(), SessionAction::EnactPause))
                         } else {
                             // Send batch
+                            if let Some(nation) = nation {
+                                claimed_nations.insert(nation.clone());
+                            }
+
                             Some((session.clone(), SessionAction::SendTelegram))
                         }
                     }
@@ -81,13 +144,10 @@ pub async fn cooldown_task(ctx: Context, data: Data) {
 
             for (session, action) in &result {
                 if *action == SessionAction::Close {
-                    sessions.remove(&session.user);
+                    sessions.remove(&(session.queue, session.user));
                 }
             }
 
-            let mut rng = rand::rng();
-            result.shuffle(&mut rng);
-
             result
         };
 
@@ -96,12 +156,18 @@ pub async fn cooldown_task(ctx: Context, data: Data) {
                 SessionAction::SendTelegram => session.try_send_new_telegram(&ctx, &data).await.unwrap_or_else(|err| {
                     warn!("Error triggering session update: {err}");
                 }),
-                SessionAction::EnactPause => session.inactivity_pause(&ctx).await.unwrap_or_else(|err| {
-                    warn!("Error triggering session pause: {err}");
-                }),
-                SessionAction::Close => session.inactivity_close(&ctx).await.unwrap_or_else(|err| {
-                    warn!("Error triggering session close: {err}");
-                }),
+                SessionAction::EnactPause => {
+                    session.insert(&data.inner.pool).await;
+                    session.inactivity_pause(&ctx).await.unwrap_or_else(|err| {
+                        warn!("Error triggering session pause: {err}");
+                    })
+                },
+                SessionAction::Close => {
+                    session.remove(&data.inner.pool).await;
+                    session.inactivity_close(&ctx).await.unwrap_or_else(|err| {
+                        warn!("Error triggering session close: {err}");
+                    })
+                },
             }
         }
     }
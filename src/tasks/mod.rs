@@ -1,6 +1,7 @@
 mod akari;
 mod cooldown;
 mod reminders;
+mod residency;
 
 use serenity::all::Context;
 use tokio::sync::OnceCell;
@@ -9,6 +10,7 @@ use crate::bot::Data;
 use cooldown::cooldown_task;
 use reminders::reminders_task;
 use akari::akari_task;
+use residency::residency_task;
 
 static BACKGROUND_TASK_LOCK: OnceCell<()> = OnceCell::const_new();
 
@@ -17,5 +19,6 @@ pub async fn spawn_background_tasks(ctx: &Context, data: &Data) {
         tokio::spawn(cooldown_task(ctx.clone(), data.clone()));
         tokio::spawn(reminders_task(ctx.clone(), data.clone()));
         tokio::spawn(akari_task(ctx.clone(), data.clone()));
+        tokio::spawn(residency_task(ctx.clone(), data.clone()));
     }).await;
 }
\ No newline at end of file
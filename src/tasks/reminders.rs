@@ -1,8 +1,10 @@
 use std::time::Duration;
-use serenity::all::{CacheHttp, Context, CreateMessage};
+use serenity::all::{CacheHttp, Context, CreateMessage, Mentionable, Timestamp};
 use log::warn;
 
 use crate::bot::Data;
+use crate::embeds::create_reminder_embed;
+use crate::models::reminder::{Reminder, ReminderRule};
 
 pub async fn reminders_task(ctx: Context, data: Data) {
     let mut ticker = tokio::time::interval(Duration::from_secs(60));
@@ -18,12 +20,39 @@ pub async fn reminders_task(ctx: Context, data: Data) {
             }).collect::<Vec<_>>()
         };
 
-        for (channel, text) in reminders {
-            if let Err(err) = channel.send_message(
-                ctx.http(), CreateMessage::new().content(&text)
-            ).await {
-                warn!("Error sending reminder for channel {channel}: {err} ({text})");
+        for reminder in reminders {
+            ReminderRule::record_reminder(reminder.rule, Timestamp::now(), &data.inner.pool).await;
+
+            let message = create_reminder_message(&ctx, &reminder).await;
+
+            if let Err(err) = reminder.channel.send_message(ctx.http(), message).await {
+                warn!("Error sending reminder for channel {}: {err} ({})", reminder.channel, reminder.text);
             }
         }
     }
-}
\ No newline at end of file
+}
+
+async fn create_reminder_message(ctx: &Context, reminder: &Reminder) -> CreateMessage {
+    if !reminder.embed {
+        return CreateMessage::new().content(&reminder.text);
+    }
+
+    // The jump link needs the queue's guild, which isn't stored with the queue
+    let guild = match reminder.queue.to_channel(ctx).await {
+        Ok(channel) => channel.guild().map(|channel| channel.guild_id),
+        Err(err) => {
+            warn!("Error resolving guild of queue {}: {err}", reminder.queue);
+            None
+        }
+    };
+
+    let link = reminder.message.link(reminder.queue, guild);
+
+    // Mentions inside embeds don't ping, so the role goes in the message content
+    let message = CreateMessage::new().embed(create_reminder_embed(reminder, &link));
+
+    match reminder.role {
+        Some(role) => message.content(role.mention().to_string()),
+        None => message,
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+use serenity::all::{CacheHttp, ChannelId, Context, CreateMessage, Mentionable, UserId};
+use log::warn;
+
+use caramel::ns::format::{canonicalize_name, prettify_name};
+
+use crate::api::{is_not_found, query_nation_data};
+use crate::bot::Data;
+
+const RESIDENCY_CHECK_INTERVAL: u64 = 60 * 60; // Registered nations are checked once an hour
+
+// Re-queries every registered sender nation, refreshing its founding time and suspending recruiters
+// whose nation left the queue's region. Suspensions are lifted once the nation is back in the region.
+pub async fn residency_task(ctx: Context, data: Data) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(RESIDENCY_CHECK_INTERVAL));
+
+    loop {
+        ticker.tick().await;
+
+        let registered = {
+            let queues = data.inner.queues.lock().await;
+
+            data.inner.user_data.lock().await.iter().filter_map(|(key, user_data)| {
+                queues.get(&key.0).map(|queue| (*key, user_data.nation.clone(), queue.region.clone()))
+            }).collect::<Vec<_>>()
+        };
+
+        for ((queue, user), nation, region) in registered {
+            let nation_data = match query_nation_data(&data.inner.api_client, &nation).await {
+                Ok(nation_data) => nation_data,
+                // The nation may have ceased to exist while the event feed was down
+                Err(err) if is_not_found(&err) => {
+                    suspend_nation(&ctx, &data, &nation, "ceased to exist").await;
+                    continue;
+                },
+                Err(err) => {
+                    warn!("Failed to re-verify residency of {}: {}", nation, err);
+                    continue;
+                }
+            };
+
+            let resides = canonicalize_name(&nation_data.region) == region;
+
+            let changed = {
+                let mut user_data = data.inner.user_data.lock().await;
+
+                let Some(user_data) = user_data.get_mut(&(queue, user)) else {
+                    continue;
+                };
+
+                // The user may have registered another nation in the meantime
+                if user_data.nation != nation {
+                    continue;
+                }
+
+                let changed = user_data.suspended == resides;
+
+                user_data.founded = nation_data.foundedtime;
+                user_data.suspended = !resides;
+                user_data.insert(&data.inner.pool).await;
+
+                changed
+            };
+
+            if !changed {
+                continue;
+            }
+
+            if resides {
+                notify(&ctx, user, format!(
+                    "{} has returned to {}, so you can recruit for {} again.",
+                    prettify_name(&nation), prettify_name(&region), queue.mention()
+                )).await;
+            } else {
+                end_sessions(&data, queue, user).await;
+
+                notify(&ctx, user, format!(
+                    "{} no longer resides in {}, so you can't recruit for {} until it moves back or you set up another nation.",
+                    prettify_name(&nation), prettify_name(&region), queue.mention()
+                )).await;
+            }
+        }
+    }
+}
+
+// Suspends every recruiter using the given nation, e.g. because it ceased to exist.
+pub async fn suspend_nation(ctx: &Context, data: &Data, nation: &str, reason: &str) {
+    let suspended = {
+        let mut user_data = data.inner.user_data.lock().await;
+        let mut suspended = Vec::new();
+
+        for (key, user_data) in user_data.iter_mut() {
+            if user_data.nation == nation && !user_data.suspended {
+                user_data.suspended = true;
+                user_data.insert(&data.inner.pool).await;
+                suspended.push(*key);
+            }
+        }
+
+        suspended
+    };
+
+    for (queue, user) in suspended {
+        end_sessions(data, queue, user).await;
+
+        notify(ctx, user, format!(
+            "{} {}, so you can't recruit for {} until you set up another nation.",
+            prettify_name(nation), reason, queue.mention()
+        )).await;
+    }
+}
+
+async fn end_sessions(data: &Data, queue: ChannelId, user: UserId) {
+    let session = data.inner.sessions.lock().await.remove(&(queue, user));
+
+    if let Some(session) = session {
+        session.remove(&data.inner.pool).await;
+    }
+}
+
+async fn notify(ctx: &Context, user: UserId, content: String) {
+    user.direct_message(ctx.http(), CreateMessage::new().content(&content)).await.map(|_| ()).unwrap_or_else(|err| {
+        warn!("Error notifying user {user} of residency change: {err} ({content})");
+    });
+}